@@ -7,33 +7,81 @@ local M = {}
 -- pre_cfg/<PLUGIN_ID | BUNDLE_ID> ... config.
 -- cfg/<PLUGIN_ID | BUNDLE_ID> ....... config.
 -- deps/<PLUGIN_ID | BUNDLE_ID> ...... depends plugin id table.
--- mods/<MODULE> ..................... plugin id table on require `<MODULE>`.
--- evs/<EVENT> ....................... plugin id table on fire `<EVENT>`.
--- fts/<FILE_TYPE> ................... plugin id table on load `<FILE_TYPE>`.
--- cmds/<COMMAND> .................... plugin id table on execute `<COMMAND>`.
--- mod_tbl ........................... configured modules.
--- ev_tbl ............................ configured events.
+-- evs/<EVENT>[@<PATTERN>] ............ plugin id table on fire `<EVENT>` (matching `<PATTERN>`).
+-- fts/<FILE_TYPE> ................... plugin id table on load `<FILE_TYPE>` (escaped as a file name).
+-- cmds/<COMMAND> .................... plugin id table on execute `<COMMAND>` (escaped as a file name).
+-- mod_idx ........................... plugin id table by module (looked up by the longest dotted prefix).
+-- ev_tbl ............................ configured events ({ event, pattern, key }).
 -- ft_tbl ............................ configured filetypes.
 -- cmd_tbl ........................... configured commands.
+-- key_tbl ........................... configured key mappings ({ mode, lhs, desc, ids }).
+-- ftdetect/<ID>/<FILE> .............. ftdetect script copied from opt plugin.
+-- ftdetect_tbl ...................... copied ftdetect scripts ({ id, group, file }).
 -- lazy .............................. plugin id table to be loaded using timer.
 -- startup ........................... startup config.
+--
+-- with `compiled`, all of the above but ftdetect scripts are fields of a single module (compiled.lua)
+-- and configs are functions instead of files.
 
 ---@class oboro-nvim.Opts
 ---@field public root string
 ---@field public lazy_time number
+---@field public compiled boolean | nil
 
 ---@type { [string]: boolean }
 local loaded_plugins = {}
 ---@type { [string]: boolean }
 local loaded_mods = {}
+---@type table | nil
+local compiled = nil
+---@type { [string]: string[] } | nil
+local mod_idx = nil
+---@type { [string]: string }
+local ftdetect_groups = {}
+
+--- escape string to be used as a file name (same as the resolver).
+---@param s string
+---@return string
+local function to_file_name(s)
+	return (s:gsub("[^%w_%-%.]", function(c)
+		return string.format("%%%02X", c:byte())
+	end))
+end
+
+--- read generated config.
+---@param opt oboro-nvim.Opts plugin option
+---@param name string config name (e.g. `deps`)
+---@param key string | nil config key (e.g. plugin id)
+---@return any
+local function read(opt, name, key)
+	if not opt.compiled then
+		return dofile(opt.root .. "/" .. name .. (key and "/" .. key or ""))
+	end
+	if compiled == nil then
+		compiled = dofile(opt.root .. "/compiled.lua")
+	end
+	if key == nil then
+		return compiled[name]
+	end
+	return compiled[name][key]
+end
 
 --- do configure.
 ---@param opt oboro-nvim.Opts plugin option
 ---@param id string plugin id
 ---@param is_pre boolean isPreconfig
 local function configure(opt, id, is_pre)
-	local path = is_pre and "/pre_cfgs/" or "/cfgs/"
-	local ok, err_msg = pcall(dofile, opt.root .. path .. id)
+	local name = is_pre and "pre_cfgs" or "cfgs"
+	local ok, err_msg
+	if opt.compiled then
+		local fn = read(opt, name, id)
+		if fn == nil then
+			return nil
+		end
+		ok, err_msg = pcall(fn)
+	else
+		ok, err_msg = pcall(dofile, opt.root .. "/" .. name .. "/" .. id)
+	end
 	if not ok then
 		print("[" .. id .. "] configure error: " .. (err_msg or "-- no msg --"))
 	end
@@ -50,16 +98,20 @@ local function load(opt, id)
 
 	configure(opt, id, true)
 
-	for _, dep in ipairs(dofile(opt.root .. "/deps/" .. id)) do
+	for _, dep in ipairs(read(opt, "deps", id)) do
 		load(opt, dep)
 	end
 
-	local plugin = dofile(opt.root .. "/plugin/" .. id)
+	local plugin = read(opt, "plugin", id)
 	if plugin ~= nil then
+		-- `packadd` sources the plugin's own ftdetect scripts again, so drop the copied ones.
+		if ftdetect_groups[id] ~= nil then
+			pcall(vim.api.nvim_del_augroup_by_name, ftdetect_groups[id])
+		end
 		vim.cmd("packadd " .. plugin)
 	end
 
-	for _, p in ipairs(dofile(opt.root .. "/plugins/" .. id)) do
+	for _, p in ipairs(read(opt, "plugins", id)) do
 		load(opt, p)
 	end
 
@@ -68,32 +120,52 @@ end
 
 ---@param opt oboro-nvim.Opts plugin option
 M.setup = function(opt)
-	dofile(opt.root .. "/startup")
+	if opt.compiled then
+		read(opt, "startup")()
+	else
+		dofile(opt.root .. "/startup")
+	end
 
 	vim.api.nvim_create_augroup("oboro", { clear = true })
 
 	-- setup event loader
-	for _, ev in ipairs(dofile(opt.root .. "/ev_tbl")) do
-		vim.api.nvim_create_autocmd({ ev }, {
+	for _, ev in ipairs(read(opt, "ev_tbl")) do
+		vim.api.nvim_create_autocmd({ ev.event }, {
 			group = "oboro",
-			pattern = "*",
+			pattern = ev.pattern,
 			once = true,
 			callback = function()
-				for _, id in ipairs(dofile(opt.root .. "/evs/" .. ev)) do
+				for _, id in ipairs(read(opt, "evs", ev.key)) do
 					load(opt, id)
 				end
 			end,
 		})
 	end
 
+	-- source ftdetect scripts of opt plugins (not in runtimepath until loaded)
+	-- in a group per plugin, which is cleared when the plugin is loaded.
+	for _, ftdetect in ipairs(read(opt, "ftdetect_tbl")) do
+		ftdetect_groups[ftdetect.id] = ftdetect.group
+		vim.cmd("augroup " .. ftdetect.group)
+		-- file names are percent-escaped, so `%` must not be expanded.
+		local ok, err_msg = pcall(vim.cmd.source, {
+			args = { opt.root .. "/ftdetect/" .. ftdetect.file },
+			magic = { file = false },
+		})
+		vim.cmd("augroup END")
+		if not ok then
+			print("[" .. ftdetect.id .. "] ftdetect error: " .. (err_msg or "-- no msg --"))
+		end
+	end
+
 	-- setup filetype loader
-	for _, ft in ipairs(dofile(opt.root .. "/ft_tbl")) do
+	for _, ft in ipairs(read(opt, "ft_tbl")) do
 		vim.api.nvim_create_autocmd({ "FileType" }, {
 			group = "oboro",
 			pattern = ft,
 			once = true,
 			callback = function()
-				for _, id in ipairs(dofile(opt.root .. "/fts/" .. ft)) do
+				for _, id in ipairs(read(opt, "fts", to_file_name(ft))) do
 					load(opt, id)
 				end
 			end,
@@ -101,19 +173,31 @@ M.setup = function(opt)
 	end
 
 	-- setup command loader
-	for _, cmd in ipairs(dofile(opt.root .. "/cmd_tbl")) do
+	for _, cmd in ipairs(read(opt, "cmd_tbl")) do
 		vim.api.nvim_create_autocmd({ "CmdUndefined" }, {
 			group = "oboro",
 			pattern = cmd,
 			once = true,
 			callback = function()
-				for _, id in ipairs(dofile(opt.root .. "/cmds/" .. cmd)) do
+				for _, id in ipairs(read(opt, "cmds", to_file_name(cmd))) do
 					load(opt, id)
 				end
 			end,
 		})
 	end
 
+	-- setup key loader
+	for _, key in ipairs(read(opt, "key_tbl")) do
+		vim.keymap.set(key.mode, key.lhs, function()
+			-- remove the stub before loading so that the plugin can map `lhs` by itself.
+			vim.keymap.del(key.mode, key.lhs)
+			for _, id in ipairs(key.ids) do
+				load(opt, id)
+			end
+			vim.api.nvim_feedkeys(vim.api.nvim_replace_termcodes(key.lhs, true, true, true), "m", false)
+		end, { desc = key.desc })
+	end
+
 	-- setup module loader
 	table.insert(package.loaders, 1, function(mod_name)
 		if loaded_mods[mod_name] then
@@ -121,19 +205,26 @@ M.setup = function(opt)
 		end
 		loaded_mods[mod_name] = true
 
-		local ok, ids = pcall(dofile, opt.root .. "/mods/" .. mod_name)
-		if not ok then
-			return nil
+		if mod_idx == nil then
+			mod_idx = read(opt, "mod_idx")
 		end
-
-		for _, id in ipairs(ids) do
-			load(opt, id)
+		-- `foo.bar.baz` -> `foo.bar` -> `foo`
+		local prefix = mod_name
+		while prefix ~= nil do
+			local ids = mod_idx[prefix]
+			if ids ~= nil then
+				for _, id in ipairs(ids) do
+					load(opt, id)
+				end
+				return nil
+			end
+			prefix = prefix:match("^(.*)%.[^.]*$")
 		end
 	end)
 
 	-- setup lazy loader
 	vim.defer_fn(function()
-		for _, id in ipairs(dofile(opt.root .. "/lazy")) do
+		for _, id in ipairs(read(opt, "lazy")) do
 			load(opt, id)
 		end
 	end, opt.lazy_time)
@@ -2,7 +2,7 @@
 { pkgs, lib }:
 let
   inherit (lib) flatten;
-  inherit (builtins) map toJSON;
+  inherit (builtins) map toJSON isString;
   inherit (import ./types.nix { inherit pkgs lib; })
     startPluginConfigDefault optPluginConfigDefault bundleConfigDefault;
 
@@ -19,6 +19,36 @@ let
     else
       x.pname;
 
+  # make event trigger.
+  #
+  # Type:
+  # --------------------
+  # (str | eventConfig) -> Event (src/config/input.rs)
+  mkEvent = x:
+    if isString x then {
+      event = x;
+      pattern = null;
+    } else {
+      inherit (x) event;
+      pattern = x.pattern or null;
+    };
+
+  # make key mapping.
+  #
+  # Type:
+  # --------------------
+  # (str | keyConfig) -> Key (src/config/input.rs)
+  mkKey = x:
+    if isString x then {
+      lhs = x;
+      modes = [ "n" ];
+      desc = null;
+    } else {
+      inherit (x) lhs;
+      modes = x.modes or [ "n" ];
+      desc = x.desc or null;
+    };
+
   # make config.
   #
   # Type:
@@ -78,12 +108,12 @@ in rec {
   # (package | startPluginConfig) -> StartPlugin (src/config/input.rs)
   toStartPlugin = plugin:
     if plugin ? plugin then {
-      inherit (plugin) plugin;
+      inherit (plugin) plugin priority before after;
       id = plugin.plugin.pname;
       startup = mkStartupCode plugin.startup;
     } else {
       inherit plugin;
-      inherit (startPluginConfigDefault) startup;
+      inherit (startPluginConfigDefault) startup priority before after;
       id = plugin.pname;
     };
 
@@ -94,7 +124,7 @@ in rec {
   # (package | optPluginConfig) -> OptPlugin (src/config/input.rs)
   toOptPlugin = plugin:
     if plugin ? plugin then {
-      inherit (plugin) plugin lazy;
+      inherit (plugin) plugin lazy priority before after;
       id = plugin.plugin.pname;
       startup = mkStartupCode plugin.startup;
       preConfig = mkConfigCode plugin.preConfig;
@@ -102,14 +132,15 @@ in rec {
       deps = map extractId plugin.depends;
       depBundles = plugin.dependBundles;
       mods = plugin.modules;
-      evs = plugin.events;
+      evs = map mkEvent plugin.events;
       fts = plugin.filetypes;
       cmds = plugin.commands;
+      keys = map mkKey plugin.keys;
     } else
       let default = optPluginConfigDefault;
       in {
         inherit plugin;
-        inherit (default) startup preConfig config lazy;
+        inherit (default) startup preConfig config lazy priority before after;
         id = plugin.pname;
         deps = default.depends;
         depBundles = default.dependBundles;
@@ -117,6 +148,7 @@ in rec {
         evs = default.events;
         fts = default.filetypes;
         cmds = default.commands;
+        keys = default.keys;
       };
 
   # adapt to `Bundle`.
@@ -126,7 +158,7 @@ in rec {
   # (str | BundleConfig) -> Bundle (src/config/input.rs)
   toBundle = bundle:
     if bundle ? name then {
-      inherit (bundle) lazy;
+      inherit (bundle) lazy priority before after;
       id = bundle.name;
       startup = mkStartupCode bundle.startup;
       preConfig = mkConfigCode bundle.preConfig;
@@ -135,13 +167,15 @@ in rec {
       deps = map extractId bundle.depends;
       depBundles = bundle.dependBundles;
       mods = bundle.modules;
-      evs = bundle.events;
+      evs = map mkEvent bundle.events;
       fts = bundle.filetypes;
       cmds = bundle.commands;
+      keys = map mkKey bundle.keys;
     } else
       let default = bundleConfigDefault;
       in {
-        inherit (default) startup preConfig config lazy plugins;
+        inherit (default)
+          startup preConfig config lazy plugins priority before after;
         id = bundle;
         deps = default.depends;
         depBundles = default.dependBundles;
@@ -149,6 +183,7 @@ in rec {
         evs = default.events;
         fts = default.filetypes;
         cmds = default.commands;
+        keys = default.keys;
       };
 
   # expand packages.
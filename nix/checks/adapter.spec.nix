@@ -11,6 +11,9 @@ in {
       id = "dummy1";
       plugin = { pname = "dummy1"; };
       startup = "";
+      priority = 0;
+      before = [ ];
+      after = [ ];
     };
   };
   test_configured_start_plugin_to_StartPlugin = {
@@ -22,6 +25,9 @@ in {
         local args = vim.json.decode([[{"start":"start"}]])
         start startup
       '';
+      priority = 100;
+      before = [ "dummy2" ];
+      after = [ ];
     };
   };
 
@@ -31,6 +37,9 @@ in {
       id = "dummy1";
       plugin = { pname = "dummy1"; };
       startup = "";
+      priority = 0;
+      before = [ ];
+      after = [ ];
       preConfig = "";
       config = "";
       deps = [ ];
@@ -39,6 +48,7 @@ in {
       evs = [ ];
       fts = [ ];
       cmds = [ ];
+      keys = [ ];
       lazy = false;
     };
   };
@@ -48,6 +58,9 @@ in {
       id = "dummy1";
       plugin = { pname = "dummy1"; };
       startup = "opt startup";
+      priority = 0;
+      before = [ ];
+      after = [ "dummy3" ];
       preConfig = ''
         vim.cmd([[
           let s:args = json_decode('{"foo":"foo"}')
@@ -61,9 +74,30 @@ in {
       deps = [ "dummy2" "dummy3" ];
       depBundles = [ "bundle1" ];
       mods = [ "module" ];
-      evs = [ "event" ];
+      evs = [
+        {
+          event = "BufReadPost";
+          pattern = null;
+        }
+        {
+          event = "User";
+          pattern = "VeryLazy";
+        }
+      ];
       fts = [ "filetype" ];
-      cmds = [ "command" ];
+      cmds = [ "Command" ];
+      keys = [
+        {
+          lhs = "<leader>o";
+          modes = [ "n" ];
+          desc = null;
+        }
+        {
+          lhs = "<leader>v";
+          modes = [ "n" "x" ];
+          desc = "visual";
+        }
+      ];
       lazy = true;
     };
   };
@@ -73,6 +107,9 @@ in {
     expected = {
       id = "dummy";
       startup = "";
+      priority = 0;
+      before = [ ];
+      after = [ ];
       preConfig = "";
       config = "";
       plugins = [ ];
@@ -82,6 +119,7 @@ in {
       evs = [ ];
       fts = [ ];
       cmds = [ ];
+      keys = [ ];
       lazy = false;
     };
   };
@@ -90,15 +128,26 @@ in {
     expected = {
       id = "dummy";
       startup = "bundle startup";
+      priority = -1;
+      before = [ ];
+      after = [ ];
       preConfig = "bundle preConfig";
       config = "bundle config";
       plugins = [ "dummy1" "dummy2" ];
       deps = [ "dummy3" "dummy4" ];
       depBundles = [ "bundle_depend_bundle" ];
       mods = [ "bundle_module" ];
-      evs = [ "bundle_event" ];
+      evs = [{
+        event = "BufEnter";
+        pattern = null;
+      }];
       fts = [ "bundle_filetype" ];
-      cmds = [ "bundle_command" ];
+      cmds = [ "BundleCommand" ];
+      keys = [{
+        lhs = "<leader>b";
+        modes = [ "n" ];
+        desc = null;
+      }];
       lazy = false;
     };
   };
@@ -20,6 +20,9 @@ in rec {
         code = "start startup";
         args = { start = "start"; };
       };
+      priority = 100;
+      before = [ "dummy2" ];
+      after = [ ];
     };
   };
 
@@ -29,6 +32,9 @@ in rec {
       type' = "plugin";
       plugin = elemAt vimPluginPackages 0;
       startup = "opt startup";
+      priority = 0;
+      before = [ ];
+      after = [ "dummy3" ];
       preConfig = {
         lang = "vim";
         code = "opt preConfig";
@@ -51,17 +57,32 @@ in rec {
           depends = [ elemAt vimPluginPackages 3 ];
           dependBundles = [ "bundle_nested" ];
           modules = [ "module_nested" ];
-          events = [ "event_nested" ];
+          events = [ "InsertEnter" ];
           filetypes = [ "filetype_nested" ];
-          commands = [ "command_nested" ];
+          commands = [ "CommandNested" ];
+          keys = [ ];
           lazy = false;
         }
       ];
       dependBundles = [ "bundle1" ];
       modules = [ "module" ];
-      events = [ "event" ];
+      events = [
+        "BufReadPost"
+        {
+          event = "User";
+          pattern = "VeryLazy";
+        }
+      ];
       filetypes = [ "filetype" ];
-      commands = [ "command" ];
+      commands = [ "Command" ];
+      keys = [
+        "<leader>o"
+        {
+          lhs = "<leader>v";
+          modes = [ "n" "x" ];
+          desc = "visual";
+        }
+      ];
       lazy = true;
     };
   };
@@ -83,13 +104,17 @@ in rec {
           depends = [ elemAt vimPluginPackages 3 ];
           dependBundles = [ "bundle_plugin_nested_bundle" ];
           modules = [ "bundle_plugin_nested_module" ];
-          events = [ "bundle_plugin_nested_event" ];
+          events = [ "CmdlineEnter" ];
           filetypes = [ "bundle_plugin_nested_filetype" ];
-          commands = [ "bundle_plugin_nested_command" ];
+          commands = [ "BundlePluginNestedCommand" ];
+          keys = [ ];
           lazy = false;
         }
       ];
       startup = "bundle startup";
+      priority = -1;
+      before = [ ];
+      after = [ ];
       preConfig = "bundle preConfig";
       config = "bundle config";
       depends = [
@@ -104,17 +129,19 @@ in rec {
           depends = [ elemAt vimPluginPackages 4 ];
           dependBundles = [ "bundle_depends_nested_bundle" ];
           modules = [ "bundle_depends_nested_module" ];
-          events = [ "bundle_depends_nested_event" ];
+          events = [ "LspAttach" ];
           filetypes = [ "bundle_depends_nested_filetype" ];
-          commands = [ "bundle_depends_nested_command" ];
+          commands = [ "BundleDependsNestedCommand" ];
+          keys = [ ];
           lazy = true;
         }
       ];
       dependBundles = [ "bundle_depend_bundle" ];
       modules = [ "bundle_module" ];
-      events = [ "bundle_event" ];
+      events = [ "BufEnter" ];
       filetypes = [ "bundle_filetype" ];
-      commands = [ "bundle_command" ];
+      commands = [ "BundleCommand" ];
+      keys = [{ lhs = "<leader>b"; }];
       lazy = false;
     };
   };
@@ -4,7 +4,7 @@ let
   inherit (builtins) toJSON map;
   inherit (pkgs) writeText stdenv;
   inherit (lib) mkIf mkEnableOption flatten;
-  inherit (lib.strings) concatStringsSep;
+  inherit (lib.strings) concatStringsSep optionalString boolToString;
   inherit (lib.lists) unique;
   inherit (stdenv) mkDerivation;
   inherit (import ./types.nix { inherit pkgs lib; })
@@ -45,19 +45,29 @@ let
       phases = [ "buildPhase" ];
       buildPhase = ''
         mkdir $out
-        ${oboro.resolver.app}/bin/oboro-resolver ${oboroJson} $out
+        ${oboro.resolver.app}/bin/oboro-resolver generate ${oboroJson} $out ${
+          optionalString cfg.compiled "--single-file"
+        } ${optionalString cfg.discoverModules "--discover-modules"} ${
+          optionalString cfg.discoverCommands "--discover-commands"
+        }
       '';
     };
   in ''
     require("oboro").setup({
       root = "${oboroRoot}",
       lazy_time = 100,
+      compiled = ${boolToString cfg.compiled},
     })
   '';
 
 in {
   options.programs.oboro-nvim = nvimConfig // oboroPluginConfig // {
     enable = mkEnableOption "oboro-nvim";
+    compiled = mkEnableOption "single-file compiled loader";
+    discoverModules =
+      mkEnableOption "module triggers discovered from `lua/` of opt plugins";
+    discoverCommands = mkEnableOption
+      "command triggers discovered from `plugin/` and `lua/` of opt plugins";
   };
   config = mkIf cfg.enable {
 
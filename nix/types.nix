@@ -5,6 +5,9 @@ in rec {
   defaults = {
     commonConfig = {
       startup = "";
+      priority = 0;
+      before = [ ];
+      after = [ ];
       extraPackages = [ ];
     };
     lazyCoreConfig = {
@@ -16,6 +19,7 @@ in rec {
       events = [ ];
       filetypes = [ ];
       commands = [ ];
+      keys = [ ];
       lazy = false;
     };
     pluginConfig = { type' = "plugin"; };
@@ -37,6 +41,21 @@ in rec {
         type = with types; either lines startupDetail;
         default = default.startup;
       };
+      priority = mkOption {
+        type = types.int;
+        description = "startup order (higher runs first).";
+        default = default.priority;
+      };
+      before = mkOption {
+        type = with types; listOf str;
+        description = "ids whose startup runs after this one.";
+        default = default.before;
+      };
+      after = mkOption {
+        type = with types; listOf str;
+        description = "ids whose startup runs before this one.";
+        default = default.after;
+      };
       extraPackages = mkOption {
         type = with types; listOf package;
         description = "nix packages.";
@@ -66,7 +85,7 @@ in rec {
         default = default.modules;
       };
       events = mkOption {
-        type = with types; listOf str;
+        type = with types; listOf (either str eventConfig);
         default = default.events;
       };
       filetypes = mkOption {
@@ -77,6 +96,10 @@ in rec {
         type = with types; listOf str;
         default = default.commands;
       };
+      keys = mkOption {
+        type = with types; listOf (either str keyConfig);
+        default = default.keys;
+      };
       lazy = mkEnableOption "lazy" // { default = default.lazy; };
     };
     pluginConfig = let default = defaults.pluginConfig;
@@ -140,6 +163,36 @@ in rec {
     };
   };
 
+  eventConfig = types.submodule {
+    options = {
+      event = mkOption {
+        type = types.str;
+        description = "autocmd event.";
+      };
+      pattern = mkOption {
+        type = with types; nullOr str;
+        default = null;
+      };
+    };
+  };
+
+  keyConfig = types.submodule {
+    options = {
+      lhs = mkOption {
+        type = types.str;
+        description = "left-hand side of the mapping.";
+      };
+      modes = mkOption {
+        type = with types; listOf str;
+        default = [ "n" ];
+      };
+      desc = mkOption {
+        type = with types; nullOr str;
+        default = null;
+      };
+    };
+  };
+
   # `start` plugin config.
   startPluginConfig =
     types.submodule { options = options.commonConfig // options.pluginConfig; };
@@ -1,11 +1,16 @@
+pub mod discover;
+pub mod events;
 pub mod input;
 pub mod output;
+pub mod syntax;
 
 use crate::config::input::OboroPluginConfig;
-use crate::config::output::{Bundle, LazyPlugin, OboroConfig, StartupPlugin};
-use anyhow::{bail, ensure, Result};
+use crate::config::output::{Bundle, Key, LazyPlugin, OboroConfig, StartupPlugin};
+use anyhow::{bail, ensure, Context, Result};
 use itertools::Itertools;
-use std::collections::{HashMap, HashSet};
+use log::{debug, info, trace, warn};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BinaryHeap, HashMap, HashSet};
 use std::hash::Hash;
 
 trait Mergeable
@@ -26,6 +31,72 @@ where
     fn key(&self) -> Key;
 }
 
+/// max width of a value shown in error messages.
+const SUMMARY_WIDTH: usize = 60;
+
+/// short summary of the first difference between two values.
+///
+/// control characters are escaped, and line breaks are shown if they are the only difference.
+fn diff_summary(x: &str, y: &str) -> String {
+    let show = |s: Option<&str>| match s {
+        None => String::from("<none>"),
+        Some(s) => {
+            let mut escaped = s
+                .chars()
+                .take(SUMMARY_WIDTH)
+                .map(|c| match c {
+                    c if c.is_control() => c.escape_default().to_string(),
+                    c => c.to_string(),
+                })
+                .collect::<String>();
+            if s.chars().count() > SUMMARY_WIDTH {
+                escaped += "...";
+            }
+            format!("`{}`", escaped)
+        }
+    };
+    let xs = x.split_inclusive('\n').collect::<Vec<_>>();
+    let ys = y.split_inclusive('\n').collect::<Vec<_>>();
+    let stripped_xs = xs
+        .iter()
+        .map(|s| s.strip_suffix('\n').unwrap_or(s))
+        .collect::<Vec<_>>();
+    let stripped_ys = ys
+        .iter()
+        .map(|s| s.strip_suffix('\n').unwrap_or(s))
+        .collect::<Vec<_>>();
+    let first_diff =
+        |xs: &[&str], ys: &[&str]| (0..xs.len().max(ys.len())).find(|&i| xs.get(i) != ys.get(i));
+    // compare without line breaks first, so that they are shown only if they are the difference.
+    let (line, x_line, y_line) = match first_diff(&stripped_xs, &stripped_ys) {
+        Some(i) => (i, stripped_xs.get(i).copied(), stripped_ys.get(i).copied()),
+        None => {
+            let i = first_diff(&xs, &ys).unwrap_or(0);
+            (i, xs.get(i).copied(), ys.get(i).copied())
+        }
+    };
+    format!(
+        "first difference at line {} ({} vs {} lines):\n  - {}\n  + {}",
+        line + 1,
+        xs.len(),
+        ys.len(),
+        show(x_line),
+        show(y_line)
+    )
+}
+
+/// merge a field of `kind` `id`, adding them to the error.
+fn merge_field<T: Mergeable>(
+    kind: &str,
+    id: &str,
+    field: &str,
+    x: &mut T,
+    y: &mut T,
+) -> Result<()> {
+    x.merge_into(y)
+        .with_context(|| format!("Conflicted `{}` of {} `{}`", field, kind, id))
+}
+
 impl Mergeable for bool {
     fn merge_into(&mut self, other: &mut Self) -> Result<()> {
         *self = *self || *other;
@@ -38,7 +109,7 @@ impl Mergeable for &str {
         let mod_self = self.modified();
         let mod_other = other.modified();
         if (mod_self && mod_other) && (self != other) {
-            bail!("Conflicted `{}`, `{}`.", self, other)
+            bail!("{}", diff_summary(self, other))
         } else if mod_other {
             std::mem::swap(self, other)
         }
@@ -46,12 +117,12 @@ impl Mergeable for &str {
     }
 }
 
-impl Mergeable for Vec<&str> {
+impl Mergeable for i64 {
     fn merge_into(&mut self, other: &mut Self) -> Result<()> {
         let mod_self = self.modified();
         let mod_other = other.modified();
-        if mod_self && mod_other {
-            bail!("Conflicted vector.")
+        if (mod_self && mod_other) && (self != other) {
+            bail!("`{}` vs `{}`", self, other)
         } else if mod_other {
             std::mem::swap(self, other)
         }
@@ -59,55 +130,127 @@ impl Mergeable for Vec<&str> {
     }
 }
 
+/// merge as an order-preserving union.
+impl Mergeable for Vec<&str> {
+    fn merge_into(&mut self, other: &mut Self) -> Result<()> {
+        for x in other.drain(..) {
+            if !self.contains(&x) {
+                self.push(x);
+            }
+        }
+        Ok(())
+    }
+}
+
 impl Mergeable for StartupPlugin<'_> {
     fn merge_into(&mut self, other: &mut Self) -> Result<()> {
+        const KIND: &str = "start plugin";
         ensure!(
             self == &Default::default() || self.id == other.id,
-            "Invalid merge ({}, {}).",
+            "Invalid merge of {} ({}, {}).",
+            KIND,
             &self.id,
             &other.id
         );
         self.id.merge_into(&mut other.id)?;
-        self.plugin.merge_into(&mut other.plugin)?;
-        self.startup.merge_into(&mut other.startup)?;
+        let id = self.id;
+        merge_field(KIND, id, "plugin", &mut self.plugin, &mut other.plugin)?;
+        merge_field(KIND, id, "startup", &mut self.startup, &mut other.startup)?;
+        merge_field(
+            KIND,
+            id,
+            "priority",
+            &mut self.priority,
+            &mut other.priority,
+        )?;
+        merge_field(KIND, id, "before", &mut self.before, &mut other.before)?;
+        merge_field(KIND, id, "after", &mut self.after, &mut other.after)?;
         Ok(())
     }
 }
 
 impl Mergeable for LazyPlugin<'_> {
     fn merge_into(&mut self, other: &mut Self) -> Result<()> {
+        const KIND: &str = "opt plugin";
         ensure!(
             self == &Default::default() || self.id == other.id,
-            "Invalid merge ({}, {}).",
+            "Invalid merge of {} ({}, {}).",
+            KIND,
             &self.id,
             &other.id
         );
         self.id.merge_into(&mut other.id)?;
-        self.plugin.merge_into(&mut other.plugin)?;
-        self.startup.merge_into(&mut other.startup)?;
-        self.pre_config.merge_into(&mut other.pre_config)?;
-        self.config.merge_into(&mut other.config)?;
-        self.deps.merge_into(&mut other.deps)?;
-        self.dep_bundles.merge_into(&mut other.dep_bundles)?;
+        let id = self.id;
+        merge_field(KIND, id, "plugin", &mut self.plugin, &mut other.plugin)?;
+        merge_field(KIND, id, "startup", &mut self.startup, &mut other.startup)?;
+        merge_field(
+            KIND,
+            id,
+            "priority",
+            &mut self.priority,
+            &mut other.priority,
+        )?;
+        merge_field(KIND, id, "before", &mut self.before, &mut other.before)?;
+        merge_field(KIND, id, "after", &mut self.after, &mut other.after)?;
+        merge_field(
+            KIND,
+            id,
+            "pre_config",
+            &mut self.pre_config,
+            &mut other.pre_config,
+        )?;
+        merge_field(KIND, id, "config", &mut self.config, &mut other.config)?;
+        merge_field(KIND, id, "deps", &mut self.deps, &mut other.deps)?;
+        merge_field(
+            KIND,
+            id,
+            "dep_bundles",
+            &mut self.dep_bundles,
+            &mut other.dep_bundles,
+        )?;
         Ok(())
     }
 }
 
 impl Mergeable for Bundle<'_> {
     fn merge_into(&mut self, other: &mut Self) -> Result<()> {
+        const KIND: &str = "bundle";
         ensure!(
             self == &Default::default() || self.id == other.id,
-            "Invalid merge ({}, {}).",
+            "Invalid merge of {} ({}, {}).",
+            KIND,
             &self.id,
             &other.id
         );
         self.id.merge_into(&mut other.id)?;
-        self.plugins.merge_into(&mut other.plugins)?;
-        self.startup.merge_into(&mut other.startup)?;
-        self.pre_config.merge_into(&mut other.pre_config)?;
-        self.config.merge_into(&mut other.config)?;
-        self.deps.merge_into(&mut other.deps)?;
-        self.dep_bundles.merge_into(&mut other.dep_bundles)?;
+        let id = self.id;
+        merge_field(KIND, id, "plugins", &mut self.plugins, &mut other.plugins)?;
+        merge_field(KIND, id, "startup", &mut self.startup, &mut other.startup)?;
+        merge_field(
+            KIND,
+            id,
+            "priority",
+            &mut self.priority,
+            &mut other.priority,
+        )?;
+        merge_field(KIND, id, "before", &mut self.before, &mut other.before)?;
+        merge_field(KIND, id, "after", &mut self.after, &mut other.after)?;
+        merge_field(
+            KIND,
+            id,
+            "pre_config",
+            &mut self.pre_config,
+            &mut other.pre_config,
+        )?;
+        merge_field(KIND, id, "config", &mut self.config, &mut other.config)?;
+        merge_field(KIND, id, "deps", &mut self.deps, &mut other.deps)?;
+        merge_field(
+            KIND,
+            id,
+            "dep_bundles",
+            &mut self.dep_bundles,
+            &mut other.dep_bundles,
+        )?;
         Ok(())
     }
 }
@@ -135,9 +278,20 @@ where
     T: Mergeable + Derivable<T, Key>,
     Key: Hash + Eq,
 {
-    xs.into_iter()
-        .into_group_map_by(|x| x.key())
-        .into_values()
+    // group by key, keeping the order of first appearance.
+    let mut index = HashMap::<Key, usize>::new();
+    let mut groups = Vec::<Vec<T>>::new();
+    for x in xs {
+        match index.get(&x.key()) {
+            Some(&i) => groups[i].push(x),
+            None => {
+                index.insert(x.key(), groups.len());
+                groups.push(vec![x]);
+            }
+        }
+    }
+    groups
+        .into_iter()
         .map(|v| {
             let def: T = Default::default();
             v.into_iter().try_fold(def, |mut acc, mut x| {
@@ -153,16 +307,16 @@ fn to_str_vector(v: &[String]) -> Vec<&str> {
     v.iter().map(|x| x.as_str()).collect()
 }
 
-/// dedup vector.
-fn to_unique_vector<T: Hash + Eq>(v: Vec<T>) -> Vec<T> {
-    v.into_iter().collect::<HashSet<_>>().into_iter().collect()
+/// dedup vector (keeps the order of first appearance).
+fn to_unique_vector<T: Hash + Eq + Clone>(v: Vec<T>) -> Vec<T> {
+    v.into_iter().unique().collect()
 }
 
 /// dedup values.
-fn to_unique_map<T: Hash + Eq>(m: HashMap<&str, Vec<T>>) -> HashMap<&str, Vec<T>> {
+fn to_unique_map<K: Ord, T: Hash + Eq + Clone>(m: BTreeMap<K, Vec<T>>) -> BTreeMap<K, Vec<T>> {
     m.into_iter()
         .map(|(k, v)| (k, to_unique_vector(v)))
-        .collect::<HashMap<&str, Vec<T>>>()
+        .collect::<BTreeMap<K, Vec<T>>>()
 }
 
 /// just mapping.
@@ -172,45 +326,70 @@ fn map(config: &OboroPluginConfig) -> OboroConfig<'_> {
     let mut lazy_plugins = Vec::new();
     let mut bundles = Vec::new();
 
-    let mut mod_map = HashMap::<&str, Vec<&str>>::new();
-    let mut ev_map = HashMap::<&str, Vec<&str>>::new();
-    let mut ft_map = HashMap::<&str, Vec<&str>>::new();
-    let mut cmd_map = HashMap::<&str, Vec<&str>>::new();
+    let mut mod_map = BTreeMap::<&str, Vec<&str>>::new();
+    let mut ev_map = BTreeMap::<(&str, &str), Vec<&str>>::new();
+    let mut ft_map = BTreeMap::<&str, Vec<&str>>::new();
+    let mut cmd_map = BTreeMap::<&str, Vec<&str>>::new();
+    let mut keys = Vec::new();
+    let mut key_map = BTreeMap::<(&str, &str), Vec<&str>>::new();
     let mut lazys = Vec::new();
 
     // start
     for plugin in config.start_plugins.iter() {
-        println!("map start plugin: {}", &plugin.id);
+        trace!(id = plugin.id.as_str(); "map start plugin");
         startup_plugins.push(StartupPlugin {
             id: &plugin.id,
             plugin: &plugin.plugin,
             startup: &plugin.startup,
+            priority: plugin.priority,
+            before: to_str_vector(&plugin.before),
+            after: to_str_vector(&plugin.after),
         });
     }
 
     // opt
     for plugin in config.opt_plugins.iter() {
-        println!("map opt plugin: {}", &plugin.id);
+        trace!(id = plugin.id.as_str(); "map opt plugin");
         lazy_plugins.push(LazyPlugin {
             id: &plugin.id,
             plugin: &plugin.plugin,
             startup: &plugin.startup,
+            priority: plugin.priority,
+            before: to_str_vector(&plugin.before),
+            after: to_str_vector(&plugin.after),
             pre_config: &plugin.pre_config,
             config: &plugin.config,
             deps: to_str_vector(&plugin.deps),
             dep_bundles: to_str_vector(&plugin.dep_bundles),
         });
         for module in to_str_vector(&plugin.mods) {
-            mod_map.entry(module).or_insert(Vec::new()).push(&plugin.id);
+            mod_map.entry(module).or_default().push(&plugin.id);
         }
-        for ev in to_str_vector(&plugin.evs) {
-            ev_map.entry(ev).or_insert(Vec::new()).push(&plugin.id);
+        for ev in plugin.evs.iter() {
+            let pattern = ev.pattern.as_deref().unwrap_or("*");
+            ev_map
+                .entry((&ev.event, pattern))
+                .or_default()
+                .push(&plugin.id);
         }
         for ft in to_str_vector(&plugin.fts) {
-            ft_map.entry(ft).or_insert(Vec::new()).push(&plugin.id);
+            ft_map.entry(ft).or_default().push(&plugin.id);
         }
         for cmd in to_str_vector(&plugin.cmds) {
-            cmd_map.entry(cmd).or_insert(Vec::new()).push(&plugin.id);
+            cmd_map.entry(cmd).or_default().push(&plugin.id);
+        }
+        for key in plugin.keys.iter() {
+            for mode in to_str_vector(&key.modes) {
+                keys.push(Key {
+                    mode,
+                    lhs: &key.lhs,
+                    desc: key.desc.as_deref(),
+                });
+                key_map
+                    .entry((mode, &key.lhs))
+                    .or_default()
+                    .push(&plugin.id);
+            }
         }
         if plugin.lazy {
             lazys.push(plugin.id.as_str());
@@ -219,27 +398,47 @@ fn map(config: &OboroPluginConfig) -> OboroConfig<'_> {
 
     // bundle
     for bundle in config.bundles.iter() {
-        println!("map bundle: {}", &bundle.id);
+        trace!(id = bundle.id.as_str(); "map bundle");
         bundles.push(Bundle {
             id: &bundle.id,
             plugins: to_str_vector(&bundle.plugins),
             startup: &bundle.startup,
+            priority: bundle.priority,
+            before: to_str_vector(&bundle.before),
+            after: to_str_vector(&bundle.after),
             pre_config: &bundle.pre_config,
             config: &bundle.config,
             deps: to_str_vector(&bundle.deps),
             dep_bundles: to_str_vector(&bundle.dep_bundles),
         });
         for module in to_str_vector(&bundle.mods) {
-            mod_map.entry(module).or_insert(Vec::new()).push(&bundle.id);
+            mod_map.entry(module).or_default().push(&bundle.id);
         }
-        for ev in to_str_vector(&bundle.evs) {
-            ev_map.entry(ev).or_insert(Vec::new()).push(&bundle.id);
+        for ev in bundle.evs.iter() {
+            let pattern = ev.pattern.as_deref().unwrap_or("*");
+            ev_map
+                .entry((&ev.event, pattern))
+                .or_default()
+                .push(&bundle.id);
         }
         for ft in to_str_vector(&bundle.fts) {
-            ft_map.entry(ft).or_insert(Vec::new()).push(&bundle.id);
+            ft_map.entry(ft).or_default().push(&bundle.id);
         }
         for cmd in to_str_vector(&bundle.cmds) {
-            cmd_map.entry(cmd).or_insert(Vec::new()).push(&bundle.id);
+            cmd_map.entry(cmd).or_default().push(&bundle.id);
+        }
+        for key in bundle.keys.iter() {
+            for mode in to_str_vector(&key.modes) {
+                keys.push(Key {
+                    mode,
+                    lhs: &key.lhs,
+                    desc: key.desc.as_deref(),
+                });
+                key_map
+                    .entry((mode, &key.lhs))
+                    .or_default()
+                    .push(&bundle.id);
+            }
         }
         if bundle.lazy {
             lazys.push(bundle.id.as_str());
@@ -254,6 +453,8 @@ fn map(config: &OboroPluginConfig) -> OboroConfig<'_> {
         ev_map,
         ft_map,
         cmd_map,
+        keys,
+        key_map,
         lazys,
         ..Default::default()
     }
@@ -285,6 +486,374 @@ fn validate(config: &OboroConfig) -> Result<()> {
     Ok(())
 }
 
+/// validate dependency graph (unknown ids and cycles).
+fn validate_graph(config: &OboroConfig) -> Result<()> {
+    let plugin_id_set = config
+        .lazy_plugins
+        .iter()
+        .map(|x| x.id)
+        .collect::<HashSet<_>>();
+    let bundle_id_set = config.bundles.iter().map(|x| x.id).collect::<HashSet<_>>();
+
+    // validate references.
+    for plugin in config.lazy_plugins.iter() {
+        for dep in plugin.deps.iter() {
+            ensure!(
+                plugin_id_set.contains(dep),
+                "`{}` depends on unknown plugin `{}`.",
+                plugin.id,
+                dep
+            );
+        }
+        for dep in plugin.dep_bundles.iter() {
+            ensure!(
+                bundle_id_set.contains(dep),
+                "`{}` depends on unknown bundle `{}`.",
+                plugin.id,
+                dep
+            );
+        }
+    }
+    for bundle in config.bundles.iter() {
+        for p in bundle.plugins.iter() {
+            ensure!(
+                plugin_id_set.contains(p),
+                "bundle `{}` contains unknown plugin `{}`.",
+                bundle.id,
+                p
+            );
+        }
+        for dep in bundle.deps.iter() {
+            ensure!(
+                plugin_id_set.contains(dep),
+                "bundle `{}` depends on unknown plugin `{}`.",
+                bundle.id,
+                dep
+            );
+        }
+        for dep in bundle.dep_bundles.iter() {
+            ensure!(
+                bundle_id_set.contains(dep),
+                "bundle `{}` depends on unknown bundle `{}`.",
+                bundle.id,
+                dep
+            );
+        }
+    }
+
+    // validate cycles.
+    let mut graph = Vec::<(&str, Vec<&str>)>::new();
+    for plugin in config.lazy_plugins.iter() {
+        graph.push((
+            plugin.id,
+            [&plugin.deps[..], &plugin.dep_bundles[..]].concat(),
+        ));
+    }
+    for bundle in config.bundles.iter() {
+        graph.push((
+            bundle.id,
+            [
+                &bundle.deps[..],
+                &bundle.dep_bundles[..],
+                &bundle.plugins[..],
+            ]
+            .concat(),
+        ));
+    }
+    if let Some(cycle) = find_cycle(&graph) {
+        bail!("dependency cycle detected: {}.", cycle.join(" -> "));
+    }
+    Ok(())
+}
+
+/// edge of loading: loading `from` also loads `to`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct LoadEdge<'a> {
+    pub from: &'a str,
+    pub to: &'a str,
+    /// `to` is a plugin of bundle `from` (not a dependency).
+    pub contains: bool,
+}
+
+/// edges of loading between opt plugins and bundles.
+pub(crate) fn load_edges<'a>(config: &OboroConfig<'a>) -> Vec<LoadEdge<'a>> {
+    let depends = |from, tos: &[&'a str]| {
+        tos.iter()
+            .map(move |&to| LoadEdge {
+                from,
+                to,
+                contains: false,
+            })
+            .collect::<Vec<_>>()
+    };
+    let mut edges = Vec::new();
+    for plugin in config.lazy_plugins.iter() {
+        edges.extend(depends(plugin.id, &plugin.deps));
+        edges.extend(depends(plugin.id, &plugin.dep_bundles));
+    }
+    for bundle in config.bundles.iter() {
+        edges.extend(depends(bundle.id, &bundle.deps));
+        edges.extend(depends(bundle.id, &bundle.dep_bundles));
+        edges.extend(bundle.plugins.iter().map(|&to| LoadEdge {
+            from: bundle.id,
+            to,
+            contains: true,
+        }));
+    }
+    edges
+}
+
+/// ids loaded (transitively) when loading `ids`, including themselves.
+fn load_closure<'a>(config: &OboroConfig<'a>, ids: &[&'a str]) -> HashSet<&'a str> {
+    let mut next = HashMap::<&str, Vec<&str>>::new();
+    for edge in load_edges(config) {
+        next.entry(edge.from).or_default().push(edge.to);
+    }
+    let mut seen = HashSet::new();
+    let mut stack = ids.to_vec();
+    while let Some(id) = stack.pop() {
+        if seen.insert(id) {
+            stack.extend(next.get(id).into_iter().flatten());
+        }
+    }
+    seen
+}
+
+/// validate module triggers.
+///
+/// requiring a module is resolved by the longest configured prefix (`foo.bar.baz` -> `foo.bar` -> `foo`),
+/// so owners of a submodule must load the owners of the enclosing module.
+fn validate_modules(config: &OboroConfig) -> Result<()> {
+    for (module, ids) in config.mod_map.iter() {
+        let loaded = load_closure(config, ids);
+        let parents = module.match_indices('.').map(|(i, _)| &module[..i]);
+        for parent in parents {
+            let Some(parent_ids) = config.mod_map.get(parent) else {
+                continue;
+            };
+            if let Some(missing) = parent_ids.iter().find(|x| !loaded.contains(*x)) {
+                bail!(
+                    "module `{}` of `{}` is inside module `{}` of `{}`, but does not load it.",
+                    module,
+                    ids.join("`, `"),
+                    parent,
+                    missing
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+/// find a cycle in the graph and return its path (e.g. `a -> b -> a`).
+fn find_cycle<'a>(graph: &[(&'a str, Vec<&'a str>)]) -> Option<Vec<&'a str>> {
+    #[derive(Clone, Copy, PartialEq, Eq)]
+    enum State {
+        Visiting,
+        Visited,
+    }
+
+    fn visit<'a>(
+        node: &'a str,
+        edges: &HashMap<&'a str, &Vec<&'a str>>,
+        states: &mut HashMap<&'a str, State>,
+        path: &mut Vec<&'a str>,
+    ) -> Option<Vec<&'a str>> {
+        match states.get(node) {
+            Some(State::Visited) => return None,
+            Some(State::Visiting) => {
+                let start = path.iter().position(|x| *x == node).unwrap_or(0);
+                let mut cycle = path[start..].to_vec();
+                cycle.push(node);
+                return Some(cycle);
+            }
+            None => {}
+        }
+        states.insert(node, State::Visiting);
+        path.push(node);
+        for next in edges.get(node).map(|v| v.as_slice()).unwrap_or_default() {
+            if let Some(cycle) = visit(next, edges, states, path) {
+                return Some(cycle);
+            }
+        }
+        path.pop();
+        states.insert(node, State::Visited);
+        None
+    }
+
+    let edges = graph
+        .iter()
+        .map(|(id, deps)| (*id, deps))
+        .collect::<HashMap<_, _>>();
+    let mut states = HashMap::new();
+    graph
+        .iter()
+        .find_map(|(id, _)| visit(id, &edges, &mut states, &mut Vec::new()))
+}
+
+/// whether `name` can be a user command (an uppercase letter followed by letters and digits).
+fn is_user_command(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_uppercase())
+        && name.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+/// whether `name` looks like a filetype (e.g. `rust`, `yaml.ansible`).
+fn is_filetype(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.')
+}
+
+/// validate names of event, filetype and command triggers.
+fn validate_triggers(config: &OboroConfig) -> Result<()> {
+    for ((ev, _), ids) in config.ev_map.iter() {
+        ensure!(
+            !ev.is_empty() && ev.chars().all(|c| c.is_ascii_alphanumeric()),
+            "invalid event `{}` of `{}`: events are made of letters and digits.",
+            ev,
+            ids.join("`, `")
+        );
+        if events::EVENTS.contains(ev) {
+            continue;
+        }
+        // autocmd events are case insensitive, so a wrong case still works.
+        // unknown events only warn, as newer neovim may know them.
+        match events::EVENTS.iter().find(|x| x.eq_ignore_ascii_case(ev)) {
+            Some(x) => warn!(
+                "event `{}` of `{}` should be `{}`.",
+                ev,
+                ids.join("`, `"),
+                x
+            ),
+            None => warn!("unknown event `{}` of `{}`.", ev, ids.join("`, `")),
+        }
+    }
+    for (cmd, ids) in config.cmd_map.iter() {
+        ensure!(
+            is_user_command(cmd),
+            "invalid command `{}` of `{}`: user commands start with an uppercase letter followed by letters and digits.",
+            cmd,
+            ids.join("`, `")
+        );
+    }
+    for (ft, ids) in config.ft_map.iter() {
+        ensure!(
+            is_filetype(ft),
+            "invalid filetype `{}` of `{}`.",
+            ft,
+            ids.join("`, `")
+        );
+    }
+    Ok(())
+}
+
+/// validate syntax of lua code.
+fn validate_syntax(config: &OboroConfig) -> Result<()> {
+    let snippets = config
+        .startup_plugins
+        .iter()
+        .map(|x| ("start plugin", x.id, "startup", x.startup))
+        .chain(config.lazy_plugins.iter().flat_map(|x| {
+            [
+                ("opt plugin", x.id, "startup", x.startup),
+                ("opt plugin", x.id, "pre_config", x.pre_config),
+                ("opt plugin", x.id, "config", x.config),
+            ]
+        }))
+        .chain(config.bundles.iter().flat_map(|x| {
+            [
+                ("bundle", x.id, "startup", x.startup),
+                ("bundle", x.id, "pre_config", x.pre_config),
+                ("bundle", x.id, "config", x.config),
+            ]
+        }));
+    for (kind, id, field, code) in snippets {
+        syntax::check(code)
+            .with_context(|| format!("Invalid lua in `{}` of {} `{}`", field, kind, id))?;
+    }
+    Ok(())
+}
+
+/// order startup snippets by `before` / `after`, then by `priority` (higher first), then by
+/// appearance.
+fn order_startups<'a>(config: &OboroConfig<'a>) -> Result<Vec<(&'a str, &'a str)>> {
+    let nodes = config
+        .startup_plugins
+        .iter()
+        .map(|x| (x.id, x.startup, x.priority, &x.before, &x.after))
+        .chain(
+            config
+                .lazy_plugins
+                .iter()
+                .map(|x| (x.id, x.startup, x.priority, &x.before, &x.after)),
+        )
+        .chain(
+            config
+                .bundles
+                .iter()
+                .map(|x| (x.id, x.startup, x.priority, &x.before, &x.after)),
+        )
+        .collect::<Vec<_>>();
+    let index = nodes
+        .iter()
+        .enumerate()
+        .map(|(i, x)| (x.0, i))
+        .collect::<HashMap<_, _>>();
+
+    // edges from the snippet that runs first.
+    let mut graph = nodes
+        .iter()
+        .map(|x| (x.0, Vec::<&str>::new()))
+        .collect::<Vec<_>>();
+    for (i, &(id, _, _, before, after)) in nodes.iter().enumerate() {
+        for &x in before.iter() {
+            let Some(&j) = index.get(x) else {
+                bail!("`{}` is ordered before unknown id `{}`.", id, x);
+            };
+            graph[i].1.push(nodes[j].0);
+        }
+        for &x in after.iter() {
+            let Some(&j) = index.get(x) else {
+                bail!("`{}` is ordered after unknown id `{}`.", id, x);
+            };
+            graph[j].1.push(id);
+        }
+    }
+    if let Some(cycle) = find_cycle(&graph) {
+        bail!("contradictory startup order: {}.", cycle.join(" -> "));
+    }
+
+    // topological sort, picking the highest priority among the ready ones.
+    let mut in_degrees = vec![0; nodes.len()];
+    for (_, nexts) in graph.iter() {
+        for next in nexts.iter() {
+            in_degrees[index[next]] += 1;
+        }
+    }
+    let mut ready = in_degrees
+        .iter()
+        .enumerate()
+        .filter(|(_, &d)| d == 0)
+        .map(|(i, _)| (nodes[i].2, Reverse(i)))
+        .collect::<BinaryHeap<_>>();
+    let mut order = Vec::new();
+    while let Some((_, Reverse(i))) = ready.pop() {
+        let (id, startup, ..) = nodes[i];
+        if !startup.is_empty() {
+            order.push((id, startup));
+        }
+        for next in graph[i].1.iter() {
+            let j = index[next];
+            in_degrees[j] -= 1;
+            if in_degrees[j] == 0 {
+                ready.push((nodes[j].2, Reverse(j)));
+            }
+        }
+    }
+    Ok(order)
+}
+
 /// resolve config.
 pub fn resolve(config: &OboroPluginConfig) -> Result<OboroConfig<'_>> {
     // pub fn resolve<'a>(config: &'a OboroPluginConfig) -> Result<OboroConfig<'a>> {
@@ -301,8 +870,22 @@ pub fn resolve(config: &OboroPluginConfig) -> Result<OboroConfig<'_>> {
     let evs = ev_map.keys().cloned().collect();
     let fts = ft_map.keys().cloned().collect();
     let cmds = cmd_map.keys().cloned().collect();
+    let key_map = to_unique_map(cfg.key_map);
+    // the first description wins.
+    let keys = key_map
+        .keys()
+        .map(|&(mode, lhs)| Key {
+            mode,
+            lhs,
+            desc: cfg
+                .keys
+                .iter()
+                .filter(|k| k.mode == mode && k.lhs == lhs)
+                .find_map(|k| k.desc),
+        })
+        .collect();
 
-    Ok(OboroConfig {
+    let mut resolved = OboroConfig {
         startup_plugins: derive(cfg.startup_plugins)?,
         lazy_plugins: derive(cfg.lazy_plugins)?,
         bundles: derive(cfg.bundles)?,
@@ -310,12 +893,39 @@ pub fn resolve(config: &OboroPluginConfig) -> Result<OboroConfig<'_>> {
         evs,
         fts,
         cmds,
+        keys,
         mod_map,
         ev_map,
         ft_map,
         cmd_map,
+        key_map,
         lazys: to_unique_vector(cfg.lazys),
-    })
+        startups: Vec::new(),
+    };
+
+    validate_graph(&resolved)?;
+    validate_modules(&resolved)?;
+    validate_triggers(&resolved)?;
+    validate_syntax(&resolved)?;
+    resolved.startups = order_startups(&resolved)?;
+
+    for plugin in resolved.startup_plugins.iter() {
+        debug!(kind = "start plugin", id = plugin.id; "resolve");
+    }
+    for plugin in resolved.lazy_plugins.iter() {
+        debug!(kind = "opt plugin", id = plugin.id; "resolve");
+    }
+    for bundle in resolved.bundles.iter() {
+        debug!(kind = "bundle", id = bundle.id; "resolve");
+    }
+    info!(
+        "resolved {} start plugins, {} opt plugins and {} bundles.",
+        resolved.startup_plugins.len(),
+        resolved.lazy_plugins.len(),
+        resolved.bundles.len()
+    );
+
+    Ok(resolved)
 }
 
 #[cfg(test)]
@@ -341,6 +951,7 @@ mod tests {
 
     #[rstest(arg, exp,
         case(vec![1, 2, 2, 3, 3, 3], vec![1, 2, 3]),
+        case(vec![3, 1, 3, 2, 1], vec![3, 1, 2]),
         case(vec![], vec![]),
     )]
     fn dedup_vector(arg: Vec<i32>, exp: Vec<i32>) {
@@ -348,10 +959,7 @@ mod tests {
         let act = to_unique_vector(arg);
 
         // assert:
-        assert_eq!(act.len(), exp.len());
-        for x in exp.iter() {
-            assert!(act.contains(x));
-        }
+        assert_eq!(act, exp);
     }
 
     #[rstest(
@@ -396,8 +1004,8 @@ mod tests {
         case(vec!["a"], vec![], vec!["a"]),
         case(vec![], vec!["a"], vec!["a"]),
         case(vec![], vec![], vec![]),
-        #[should_panic]
-        case(vec!["a"], vec!["a"], vec![]),
+        case(vec!["a"], vec!["a"], vec!["a"]),
+        case(vec!["b", "a"], vec!["c", "a", "d"], vec!["b", "a", "c", "d"]),
     )]
     fn merge_vec(arg_x: Vec<&str>, arg_y: Vec<&str>, exp: Vec<&str>) {
         // arrange:
@@ -413,26 +1021,26 @@ mod tests {
 
     #[rstest(arg_x, arg_y, exp,
         case(
-            StartupPlugin { id : "foo", plugin : "plugin", startup: "startup" },
-            StartupPlugin { id : "foo", plugin : "", startup: "" },
-            StartupPlugin { id : "foo", plugin : "plugin", startup: "startup" }
+            StartupPlugin { id : "foo", plugin : "plugin", startup: "startup", ..Default::default() },
+            StartupPlugin { id : "foo", plugin : "", startup: "", ..Default::default() },
+            StartupPlugin { id : "foo", plugin : "plugin", startup: "startup", ..Default::default() }
         ),
         case(
-            StartupPlugin { id : "foo", plugin : "", startup: "" },
-            StartupPlugin { id : "foo", plugin : "plugin", startup: "startup" },
-            StartupPlugin { id : "foo", plugin : "plugin", startup: "startup" }
+            StartupPlugin { id : "foo", plugin : "", startup: "", ..Default::default() },
+            StartupPlugin { id : "foo", plugin : "plugin", startup: "startup", ..Default::default() },
+            StartupPlugin { id : "foo", plugin : "plugin", startup: "startup", ..Default::default() }
         ),
         #[should_panic]
         case(
-            StartupPlugin { id : "foo", plugin : "", startup: "" },
-            StartupPlugin { id : "bar", plugin : "", startup: "" },
-            StartupPlugin { id : "_", plugin : "_", startup: "_" }
+            StartupPlugin { id : "foo", plugin : "", startup: "", ..Default::default() },
+            StartupPlugin { id : "bar", plugin : "", startup: "", ..Default::default() },
+            StartupPlugin { id : "_", plugin : "_", startup: "_", ..Default::default() }
         ),
         #[should_panic]
         case(
-            StartupPlugin { id : "foo", plugin : "p", startup: "startup1" },
-            StartupPlugin { id : "foo", plugin : "p", startup: "startup2" },
-            StartupPlugin { id : "_", plugin : "_", startup: "_" }
+            StartupPlugin { id : "foo", plugin : "p", startup: "startup1", ..Default::default() },
+            StartupPlugin { id : "foo", plugin : "p", startup: "startup2", ..Default::default() },
+            StartupPlugin { id : "_", plugin : "_", startup: "_", ..Default::default() }
         ),
     )]
     fn merge_startup(arg_x: StartupPlugin, arg_y: StartupPlugin, exp: StartupPlugin) {
@@ -449,31 +1057,36 @@ mod tests {
 
     #[rstest(arg_x, arg_y, exp,
         case(
-            LazyPlugin { id : "foo", plugin : "plugin", startup: "startup",pre_config: "preconfig", config: "config", deps: vec!["bar"], dep_bundles: vec!["baz"] },
-            LazyPlugin { id : "foo", plugin : "",       startup: "",       pre_config: "preconfig", config: "config", deps: vec![],      dep_bundles: vec![] },
-            LazyPlugin { id : "foo", plugin : "plugin", startup: "startup",pre_config: "preconfig", config: "config", deps: vec!["bar"], dep_bundles: vec!["baz"] },
+            LazyPlugin { id : "foo", plugin : "plugin", startup: "startup",pre_config: "preconfig", config: "config", deps: vec!["bar"], dep_bundles: vec!["baz"], ..Default::default() },
+            LazyPlugin { id : "foo", plugin : "",       startup: "",       pre_config: "preconfig", config: "config", deps: vec![],      dep_bundles: vec![], ..Default::default() },
+            LazyPlugin { id : "foo", plugin : "plugin", startup: "startup",pre_config: "preconfig", config: "config", deps: vec!["bar"], dep_bundles: vec!["baz"], ..Default::default() },
         ),
         case(
-            LazyPlugin { id : "foo", plugin : "",       startup: "",       pre_config: "", config: "", deps: vec![],      dep_bundles: vec![] },
-            LazyPlugin { id : "foo", plugin : "plugin", startup: "startup",pre_config: "preconfig", config: "config", deps: vec!["bar"], dep_bundles: vec!["baz"] },
-            LazyPlugin { id : "foo", plugin : "plugin", startup: "startup",pre_config: "preconfig", config: "config", deps: vec!["bar"], dep_bundles: vec!["baz"] },
+            LazyPlugin { id : "foo", plugin : "",       startup: "",       pre_config: "", config: "", deps: vec![],      dep_bundles: vec![], ..Default::default() },
+            LazyPlugin { id : "foo", plugin : "plugin", startup: "startup",pre_config: "preconfig", config: "config", deps: vec!["bar"], dep_bundles: vec!["baz"], ..Default::default() },
+            LazyPlugin { id : "foo", plugin : "plugin", startup: "startup",pre_config: "preconfig", config: "config", deps: vec!["bar"], dep_bundles: vec!["baz"], ..Default::default() },
         ),
         case(
-            LazyPlugin { id : "foo", plugin : "plugin", startup: "startup",pre_config: "preconfig", config: "config", deps: vec!["bar"], dep_bundles: vec!["baz"] },
-            LazyPlugin { id : "foo", plugin : "",       startup: "",       pre_config: "", config: "", deps: vec![],      dep_bundles: vec![] },
-            LazyPlugin { id : "foo", plugin : "plugin", startup: "startup",pre_config: "preconfig", config: "config", deps: vec!["bar"], dep_bundles: vec!["baz"] },
+            LazyPlugin { id : "foo", plugin : "plugin", startup: "startup",pre_config: "preconfig", config: "config", deps: vec!["bar"], dep_bundles: vec!["baz"], ..Default::default() },
+            LazyPlugin { id : "foo", plugin : "",       startup: "",       pre_config: "", config: "", deps: vec![],      dep_bundles: vec![], ..Default::default() },
+            LazyPlugin { id : "foo", plugin : "plugin", startup: "startup",pre_config: "preconfig", config: "config", deps: vec!["bar"], dep_bundles: vec!["baz"], ..Default::default() },
         ),
         #[should_panic]
         case(
-            LazyPlugin { id : "foo",  plugin : "plugin", startup: "startup", pre_config: "preconfig", config: "config", deps: vec!["bar"], dep_bundles: vec!["baz"] },
-            LazyPlugin { id : "hoge", plugin : "",       startup: "",        pre_config: "_", config: "",       deps: vec![],      dep_bundles: vec![] },
-            LazyPlugin { id : "_",    plugin : "_",      startup: "_",       pre_config: "_", config: "_",      deps: vec![],      dep_bundles: vec![] }
+            LazyPlugin { id : "foo",  plugin : "plugin", startup: "startup", pre_config: "preconfig", config: "config", deps: vec!["bar"], dep_bundles: vec!["baz"], ..Default::default() },
+            LazyPlugin { id : "hoge", plugin : "",       startup: "",        pre_config: "_", config: "",       deps: vec![],      dep_bundles: vec![], ..Default::default() },
+            LazyPlugin { id : "_",    plugin : "_",      startup: "_",       pre_config: "_", config: "_",      deps: vec![],      dep_bundles: vec![], ..Default::default() }
+        ),
+        case(
+            LazyPlugin { id : "foo", plugin : "_", startup: "_", pre_config: "_", config: "_", deps: vec!["foo1"],         dep_bundles: vec!["bar1"], ..Default::default() },
+            LazyPlugin { id : "foo", plugin : "_", startup: "_", pre_config: "_", config: "_", deps: vec!["foo2", "foo1"], dep_bundles: vec!["bar2"], ..Default::default() },
+            LazyPlugin { id : "foo", plugin : "_", startup: "_", pre_config: "_", config: "_", deps: vec!["foo1", "foo2"], dep_bundles: vec!["bar1", "bar2"], ..Default::default() }
         ),
         #[should_panic]
         case(
-            LazyPlugin { id : "foo", plugin : "_", startup: "_", pre_config: "_", config: "_", deps: vec!["conflict_foo1"], dep_bundles: vec![] },
-            LazyPlugin { id : "foo", plugin : "_", startup: "_", pre_config: "_", config: "_", deps: vec!["conflict_foo2"], dep_bundles: vec![] },
-            LazyPlugin { id : "_",   plugin : "_", startup: "_", pre_config: "_", config: "_", deps: vec![],                dep_bundles: vec![] }
+            LazyPlugin { id : "foo", plugin : "_", startup: "_", pre_config: "_", config: "config1", deps: vec![], dep_bundles: vec![], ..Default::default() },
+            LazyPlugin { id : "foo", plugin : "_", startup: "_", pre_config: "_", config: "config2", deps: vec![], dep_bundles: vec![], ..Default::default() },
+            LazyPlugin { id : "_",   plugin : "_", startup: "_", pre_config: "_", config: "_",       deps: vec![], dep_bundles: vec![], ..Default::default() }
         ),
     )]
     fn merge_lazy(arg_x: LazyPlugin, arg_y: LazyPlugin, exp: LazyPlugin) {
@@ -490,26 +1103,31 @@ mod tests {
 
     #[rstest(arg_x, arg_y, exp,
         case(
-            Bundle { id : "foo", plugins : vec!["plugins"], startup: "startup", pre_config: "preconfig", config: "config", deps: vec!["bar"], dep_bundles: vec!["baz"] },
-            Bundle { id : "foo", plugins : vec![],          startup: "",        pre_config: "", config: "config", deps: vec![],      dep_bundles: vec![] },
-            Bundle { id : "foo", plugins : vec!["plugins"], startup: "startup", pre_config: "preconfig", config: "config", deps: vec!["bar"], dep_bundles: vec!["baz"] },
+            Bundle { id : "foo", plugins : vec!["plugins"], startup: "startup", pre_config: "preconfig", config: "config", deps: vec!["bar"], dep_bundles: vec!["baz"], ..Default::default() },
+            Bundle { id : "foo", plugins : vec![],          startup: "",        pre_config: "", config: "config", deps: vec![],      dep_bundles: vec![], ..Default::default() },
+            Bundle { id : "foo", plugins : vec!["plugins"], startup: "startup", pre_config: "preconfig", config: "config", deps: vec!["bar"], dep_bundles: vec!["baz"], ..Default::default() },
         ),
         case(
-            Bundle { id : "foo", plugins : vec![],          startup: "",        pre_config: "", config: "config", deps: vec![],      dep_bundles: vec![] },
-            Bundle { id : "foo", plugins : vec!["plugins"], startup: "startup", pre_config: "preconfig", config: "config", deps: vec!["bar"], dep_bundles: vec!["baz"] },
-            Bundle { id : "foo", plugins : vec!["plugins"], startup: "startup", pre_config: "preconfig", config: "config", deps: vec!["bar"], dep_bundles: vec!["baz"] },
+            Bundle { id : "foo", plugins : vec![],          startup: "",        pre_config: "", config: "config", deps: vec![],      dep_bundles: vec![], ..Default::default() },
+            Bundle { id : "foo", plugins : vec!["plugins"], startup: "startup", pre_config: "preconfig", config: "config", deps: vec!["bar"], dep_bundles: vec!["baz"], ..Default::default() },
+            Bundle { id : "foo", plugins : vec!["plugins"], startup: "startup", pre_config: "preconfig", config: "config", deps: vec!["bar"], dep_bundles: vec!["baz"], ..Default::default() },
         ),
         #[should_panic]
         case(
-            Bundle { id : "foo",  plugins : vec!["plugins"], startup: "startup", pre_config: "preconfig", config: "config", deps: vec!["bar"], dep_bundles: vec!["baz"] },
-            Bundle { id : "hoge", plugins : vec![],          startup: "",        pre_config: "", config: "",       deps: vec![],      dep_bundles: vec![] },
-            Bundle { id : "_",    plugins : vec![],          startup: "_",       pre_config: "_", config: "_",      deps: vec![],      dep_bundles: vec![] }
+            Bundle { id : "foo",  plugins : vec!["plugins"], startup: "startup", pre_config: "preconfig", config: "config", deps: vec!["bar"], dep_bundles: vec!["baz"], ..Default::default() },
+            Bundle { id : "hoge", plugins : vec![],          startup: "",        pre_config: "", config: "",       deps: vec![],      dep_bundles: vec![], ..Default::default() },
+            Bundle { id : "_",    plugins : vec![],          startup: "_",       pre_config: "_", config: "_",      deps: vec![],      dep_bundles: vec![], ..Default::default() }
+        ),
+        case(
+            Bundle { id : "foo", plugins : vec!["p1"], startup: "_", pre_config: "_", config: "_", deps: vec!["foo1"], dep_bundles: vec![], ..Default::default() },
+            Bundle { id : "foo", plugins : vec!["p2"], startup: "_", pre_config: "_", config: "_", deps: vec!["foo2"], dep_bundles: vec!["bar"], ..Default::default() },
+            Bundle { id : "foo", plugins : vec!["p1", "p2"], startup: "_", pre_config: "_", config: "_", deps: vec!["foo1", "foo2"], dep_bundles: vec!["bar"], ..Default::default() }
         ),
         #[should_panic]
         case(
-            Bundle { id : "foo", plugins : vec![], startup: "_", pre_config: "_", config: "_", deps: vec!["conflict_foo1"], dep_bundles: vec![] },
-            Bundle { id : "foo", plugins : vec![], startup: "_", pre_config: "_", config: "_", deps: vec!["conflict_foo2"], dep_bundles: vec![] },
-            Bundle { id : "_",   plugins : vec![], startup: "_", pre_config: "_", config: "_", deps: vec![], dep_bundles: vec![] }
+            Bundle { id : "foo", plugins : vec![], startup: "startup1", pre_config: "_", config: "_", deps: vec![], dep_bundles: vec![], ..Default::default() },
+            Bundle { id : "foo", plugins : vec![], startup: "startup2", pre_config: "_", config: "_", deps: vec![], dep_bundles: vec![], ..Default::default() },
+            Bundle { id : "_",   plugins : vec![], startup: "_",        pre_config: "_", config: "_", deps: vec![], dep_bundles: vec![], ..Default::default() }
         ),
     )]
     fn merge_bundle(arg_x: Bundle, arg_y: Bundle, exp: Bundle) {
@@ -527,14 +1145,14 @@ mod tests {
     #[rstest(arg, exp,
         case(
             vec![
-                StartupPlugin { id : "foo", plugin : "", startup: "" },
-                StartupPlugin { id : "bar", plugin : "", startup: "" },
-                StartupPlugin { id : "foo", plugin : "foo plugin", startup: "foo startup" },
-                StartupPlugin { id : "bar", plugin : "foo plugin", startup: "foo startup" },
+                StartupPlugin { id : "foo", plugin : "", startup: "", ..Default::default() },
+                StartupPlugin { id : "bar", plugin : "", startup: "", ..Default::default() },
+                StartupPlugin { id : "foo", plugin : "foo plugin", startup: "foo startup", ..Default::default() },
+                StartupPlugin { id : "bar", plugin : "foo plugin", startup: "foo startup", ..Default::default() },
             ],
             vec![
-                StartupPlugin { id : "foo", plugin : "foo plugin", startup: "foo startup" },
-                StartupPlugin { id : "bar", plugin : "foo plugin", startup: "foo startup" },
+                StartupPlugin { id : "foo", plugin : "foo plugin", startup: "foo startup", ..Default::default() },
+                StartupPlugin { id : "bar", plugin : "foo plugin", startup: "foo startup", ..Default::default() },
             ],
         ),
         case(vec![],vec![]),
@@ -551,17 +1169,51 @@ mod tests {
         assert_eq!(act, exp);
     }
 
+    #[test]
+    fn derive_keeps_order() {
+        // arrange:
+        let arg = vec![
+            StartupPlugin {
+                id: "c",
+                ..Default::default()
+            },
+            StartupPlugin {
+                id: "a",
+                ..Default::default()
+            },
+            StartupPlugin {
+                id: "c",
+                startup: "c startup",
+                ..Default::default()
+            },
+            StartupPlugin {
+                id: "b",
+                ..Default::default()
+            },
+        ];
+
+        // act:
+        let act = derive(arg).unwrap();
+
+        // assert:
+        assert_eq!(
+            act.iter().map(|x| x.id).collect::<Vec<_>>(),
+            vec!["c", "a", "b"]
+        );
+        assert_eq!(act[0].startup, "c startup");
+    }
+
     #[rstest(arg, exp,
         case(
             vec![
-                LazyPlugin { id : "foo", plugin : "", startup: "", pre_config: "", config: "", deps: vec![], dep_bundles: vec![] },
-                LazyPlugin { id : "bar", plugin : "", startup: "", pre_config: "", config: "", deps: vec![], dep_bundles: vec![] },
-                LazyPlugin { id : "foo", plugin : "foo plugin", startup: "foo startup", pre_config: "foo pre config", config: "foo config", deps: vec!["foo"], dep_bundles: vec!["foo_dep"] },
-                LazyPlugin { id : "bar", plugin : "bar plugin", startup: "bar startup", pre_config: "bar pre config", config: "bar config", deps: vec!["foo"], dep_bundles: vec!["bar_dep"] },
+                LazyPlugin { id : "foo", plugin : "", startup: "", pre_config: "", config: "", deps: vec![], dep_bundles: vec![], ..Default::default() },
+                LazyPlugin { id : "bar", plugin : "", startup: "", pre_config: "", config: "", deps: vec![], dep_bundles: vec![], ..Default::default() },
+                LazyPlugin { id : "foo", plugin : "foo plugin", startup: "foo startup", pre_config: "foo pre config", config: "foo config", deps: vec!["foo"], dep_bundles: vec!["foo_dep"], ..Default::default() },
+                LazyPlugin { id : "bar", plugin : "bar plugin", startup: "bar startup", pre_config: "bar pre config", config: "bar config", deps: vec!["foo"], dep_bundles: vec!["bar_dep"], ..Default::default() },
             ],
             vec![
-                LazyPlugin { id : "foo", plugin : "foo plugin", startup: "foo startup", pre_config: "foo pre config", config: "foo config", deps: vec!["foo"], dep_bundles: vec!["foo_dep"] },
-                LazyPlugin { id : "bar", plugin : "bar plugin", startup: "bar startup", pre_config: "bar pre config", config: "bar config", deps: vec!["foo"], dep_bundles: vec!["bar_dep"] },
+                LazyPlugin { id : "foo", plugin : "foo plugin", startup: "foo startup", pre_config: "foo pre config", config: "foo config", deps: vec!["foo"], dep_bundles: vec!["foo_dep"], ..Default::default() },
+                LazyPlugin { id : "bar", plugin : "bar plugin", startup: "bar startup", pre_config: "bar pre config", config: "bar config", deps: vec!["foo"], dep_bundles: vec!["bar_dep"], ..Default::default() },
             ],
         ),
         case(vec![],vec![]),
@@ -581,14 +1233,14 @@ mod tests {
     #[rstest(arg, exp,
         case(
             vec![
-                Bundle { id : "foo", plugins : vec![], startup: "", pre_config: "", config: "", deps: vec![], dep_bundles: vec![] },
-                Bundle { id : "bar", plugins : vec![], startup: "", pre_config: "", config: "", deps: vec![], dep_bundles: vec![] },
-                Bundle { id : "foo", plugins : vec!["foo_plugins"], startup: "foo startup", pre_config: "foo pre config", config: "foo config", deps: vec!["foo"], dep_bundles: vec!["foo_dep"] },
-                Bundle { id : "bar", plugins : vec!["bar_plugins"], startup: "bar startup", pre_config: "bar pre config", config: "bar config", deps: vec!["foo"], dep_bundles: vec!["bar_dep"] },
+                Bundle { id : "foo", plugins : vec![], startup: "", pre_config: "", config: "", deps: vec![], dep_bundles: vec![], ..Default::default() },
+                Bundle { id : "bar", plugins : vec![], startup: "", pre_config: "", config: "", deps: vec![], dep_bundles: vec![], ..Default::default() },
+                Bundle { id : "foo", plugins : vec!["foo_plugins"], startup: "foo startup", pre_config: "foo pre config", config: "foo config", deps: vec!["foo"], dep_bundles: vec!["foo_dep"], ..Default::default() },
+                Bundle { id : "bar", plugins : vec!["bar_plugins"], startup: "bar startup", pre_config: "bar pre config", config: "bar config", deps: vec!["foo"], dep_bundles: vec!["bar_dep"], ..Default::default() },
             ],
             vec![
-                Bundle { id : "foo", plugins : vec!["foo_plugins"], startup: "foo startup", pre_config: "foo pre config", config: "foo config", deps: vec!["foo"], dep_bundles: vec!["foo_dep"] },
-                Bundle { id : "bar", plugins : vec!["bar_plugins"], startup: "bar startup", pre_config: "bar pre config", config: "bar config", deps: vec!["foo"], dep_bundles: vec!["bar_dep"] },
+                Bundle { id : "foo", plugins : vec!["foo_plugins"], startup: "foo startup", pre_config: "foo pre config", config: "foo config", deps: vec!["foo"], dep_bundles: vec!["foo_dep"], ..Default::default() },
+                Bundle { id : "bar", plugins : vec!["bar_plugins"], startup: "bar startup", pre_config: "bar pre config", config: "bar config", deps: vec!["foo"], dep_bundles: vec!["bar_dep"], ..Default::default() },
             ],
         ),
         case(vec![],vec![]),
@@ -639,6 +1291,192 @@ mod tests {
         validate(&arg).unwrap();
     }
 
+    #[rstest(arg,
+        case(
+            OboroConfig {
+                lazy_plugins: vec![
+                    LazyPlugin {id: "foo", deps: vec!["bar"], dep_bundles: vec!["baz"], ..Default::default()},
+                    LazyPlugin {id: "bar", ..Default::default()},
+                ],
+                bundles: vec![Bundle {id: "baz", plugins: vec!["bar"], ..Default::default()},], ..Default::default()
+            }
+        ),
+    #[should_panic]
+        case(
+            OboroConfig {
+                lazy_plugins: vec![LazyPlugin {id: "foo", deps: vec!["unknown"], ..Default::default()},], ..Default::default()
+            }
+        ),
+    #[should_panic]
+        case(
+            OboroConfig {
+                lazy_plugins: vec![LazyPlugin {id: "foo", dep_bundles: vec!["unknown"], ..Default::default()},], ..Default::default()
+            }
+        ),
+    #[should_panic]
+        case(
+            OboroConfig {
+                bundles: vec![Bundle {id: "foo", plugins: vec!["unknown"], ..Default::default()},], ..Default::default()
+            }
+        ),
+    #[should_panic]
+        case(
+            OboroConfig {
+                lazy_plugins: vec![
+                    LazyPlugin {id: "foo", deps: vec!["bar"], ..Default::default()},
+                    LazyPlugin {id: "bar", deps: vec!["foo"], ..Default::default()},
+                ], ..Default::default()
+            }
+        ),
+    // a plugin depending on a bundle containing itself would be loaded after the bundle's config.
+    #[should_panic]
+        case(
+            OboroConfig {
+                lazy_plugins: vec![LazyPlugin {id: "foo", dep_bundles: vec!["baz"], ..Default::default()},],
+                bundles: vec![Bundle {id: "baz", plugins: vec!["foo"], ..Default::default()},], ..Default::default()
+            }
+        ),
+    #[should_panic]
+        case(
+            OboroConfig {
+                lazy_plugins: vec![
+                    LazyPlugin {id: "foo", dep_bundles: vec!["baz"], ..Default::default()},
+                    LazyPlugin {id: "bar", deps: vec!["foo"], ..Default::default()},
+                ],
+                bundles: vec![Bundle {id: "baz", plugins: vec!["foo", "bar"], ..Default::default()},], ..Default::default()
+            }
+        ),
+     )]
+    fn validate_config_graph(arg: OboroConfig) {
+        validate_graph(&arg).unwrap();
+    }
+
+    #[test]
+    fn validate_config_graph_self_bundle() {
+        // arrange:
+        let config = OboroConfig {
+            lazy_plugins: vec![LazyPlugin {
+                id: "foo",
+                dep_bundles: vec!["baz"],
+                ..Default::default()
+            }],
+            bundles: vec![Bundle {
+                id: "baz",
+                plugins: vec!["foo"],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        // act:
+        let act = validate_graph(&config).unwrap_err();
+
+        // assert:
+        assert_eq!(
+            act.to_string(),
+            "dependency cycle detected: foo -> baz -> foo."
+        );
+    }
+
+    #[rstest(ev_map, ft_map, cmd_map, exp,
+        case(vec![("BufReadPost", "*"), ("User", "VeryLazy")], vec!["rust", "yaml.ansible"], vec!["Foo", "Foo2"], None),
+        // wrong case only warns.
+        case(vec![("BufReadpost", "*")], vec![], vec![], None),
+        // unknown events only warn (e.g. added in newer neovim).
+        case(vec![("BufReadPst", "*")], vec![], vec![], None),
+        case(vec![("BufRead,BufNewFile", "*")], vec![], vec![], Some("invalid event `BufRead,BufNewFile` of `foo`, `bar`: events are made of letters and digits.")),
+        case(vec![("", "*")], vec![], vec![], Some("invalid event `` of `foo`, `bar`: events are made of letters and digits.")),
+        case(vec![], vec!["rust lang"], vec![], Some("invalid filetype `rust lang` of `foo`, `bar`.")),
+        case(vec![], vec![""], vec![], Some("invalid filetype `` of `foo`, `bar`.")),
+        case(vec![], vec![], vec!["telescope"], Some("invalid command `telescope` of `foo`, `bar`: user commands start with an uppercase letter followed by letters and digits.")),
+        case(vec![], vec![], vec!["Foo_Bar"], Some("invalid command `Foo_Bar` of `foo`, `bar`: user commands start with an uppercase letter followed by letters and digits.")),
+    )]
+    fn validate_config_triggers(
+        ev_map: Vec<(&'static str, &'static str)>,
+        ft_map: Vec<&'static str>,
+        cmd_map: Vec<&'static str>,
+        exp: Option<&str>,
+    ) {
+        // arrange:
+        let ids = vec!["foo", "bar"];
+        let config = OboroConfig {
+            ev_map: ev_map.into_iter().map(|x| (x, ids.clone())).collect(),
+            ft_map: ft_map.into_iter().map(|x| (x, ids.clone())).collect(),
+            cmd_map: cmd_map.into_iter().map(|x| (x, ids.clone())).collect(),
+            ..Default::default()
+        };
+
+        // act:
+        let act = validate_triggers(&config);
+
+        // assert:
+        assert_eq!(act.err().map(|x| x.to_string()).as_deref(), exp);
+    }
+
+    #[rstest(mod_map, exp,
+        case(vec![("foo", vec!["foo"]), ("bar.baz", vec!["bar"])], None),
+        // an extension owning a submodule depends on the owner of the module.
+        case(vec![("foo", vec!["foo"]), ("foo._extensions.ext", vec!["ext"])], None),
+        // a bundle loads its plugins.
+        case(vec![("foo", vec!["foo"]), ("foo.sub", vec!["bundle"])], None),
+        case(vec![("foo", vec!["foo"]), ("foo.sub", vec!["foo"])], None),
+        case(
+            vec![("foo", vec!["foo"]), ("foo.sub", vec!["bar"])],
+            Some("module `foo.sub` of `bar` is inside module `foo` of `foo`, but does not load it.")
+        ),
+        case(
+            vec![("foo", vec!["foo", "bar"]), ("foo.a.b", vec!["ext"])],
+            Some("module `foo.a.b` of `ext` is inside module `foo` of `bar`, but does not load it.")
+        ),
+    )]
+    fn validate_config_modules(mod_map: Vec<(&'static str, Vec<&'static str>)>, exp: Option<&str>) {
+        // arrange:
+        let config = OboroConfig {
+            lazy_plugins: vec![
+                LazyPlugin {
+                    id: "foo",
+                    ..Default::default()
+                },
+                LazyPlugin {
+                    id: "bar",
+                    ..Default::default()
+                },
+                LazyPlugin {
+                    id: "ext",
+                    deps: vec!["foo"],
+                    ..Default::default()
+                },
+            ],
+            bundles: vec![Bundle {
+                id: "bundle",
+                plugins: vec!["foo"],
+                ..Default::default()
+            }],
+            mod_map: mod_map.into_iter().collect(),
+            ..Default::default()
+        };
+
+        // act:
+        let act = validate_modules(&config);
+
+        // assert:
+        assert_eq!(act.err().map(|x| x.to_string()).as_deref(), exp);
+    }
+
+    #[rstest(arg, exp,
+        case(vec![("a", vec!["b"]), ("b", vec!["c"]), ("c", vec![])], None),
+        case(vec![("a", vec!["a"])], Some(vec!["a", "a"])),
+        case(vec![("a", vec!["b"]), ("b", vec!["c"]), ("c", vec!["b"])], Some(vec!["b", "c", "b"])),
+        case(vec![], None),
+    )]
+    fn detect_cycle(arg: Vec<(&str, Vec<&str>)>, exp: Option<Vec<&str>>) {
+        // act:
+        let act = find_cycle(&arg);
+
+        // assert:
+        assert_eq!(act, exp);
+    }
+
     #[test]
     fn map_config() {
         // arrange:
@@ -647,20 +1485,34 @@ mod tests {
                 id: String::from("foo"),
                 plugin: String::from("foo_plugin"),
                 startup: String::from("foo startup"),
+                priority: 10,
+                before: vec![String::from("bar")],
+                after: vec![],
             }],
             opt_plugins: vec![
                 input::OptPlugin {
                     id: String::from("bar"),
                     plugin: String::from("bar_plugin"),
                     startup: String::from("bar startup"),
+                    priority: 0,
+                    before: vec![],
+                    after: vec![String::from("foo")],
                     pre_config: String::from("bar pre config"),
                     config: String::from("bar config"),
                     deps: vec![String::from("baz")],
                     dep_bundles: vec![String::from("hoge")],
                     cmds: vec![String::from("bar_cmd")],
                     mods: vec![String::from("bar_mod")],
-                    evs: vec![String::from("bar_ev")],
+                    evs: vec![input::Event {
+                        event: String::from("bar_ev"),
+                        pattern: None,
+                    }],
                     fts: vec![String::from("bar_ft")],
+                    keys: vec![input::Key {
+                        lhs: String::from("bar_key"),
+                        modes: vec![String::from("n"), String::from("x")],
+                        desc: Some(String::from("bar desc")),
+                    }],
                     lazy: true,
                 },
                 input::OptPlugin {
@@ -685,14 +1537,25 @@ mod tests {
                     id: String::from("hoge"),
                     plugins: vec![String::from("bar"), String::from("qux")],
                     startup: String::from("hoge startup"),
+                    priority: -1,
+                    before: vec![],
+                    after: vec![],
                     pre_config: String::from("hoge pre config"),
                     config: String::from("hoge config"),
                     deps: vec![String::from("quux")],
                     dep_bundles: vec![String::from("huga")],
                     cmds: vec![String::from("hoge_cmd")],
                     mods: vec![String::from("hoge_mod")],
-                    evs: vec![String::from("hoge_ev")],
+                    evs: vec![input::Event {
+                        event: String::from("hoge_ev"),
+                        pattern: Some(String::from("*.rs")),
+                    }],
                     fts: vec![String::from("hoge_ft")],
+                    keys: vec![input::Key {
+                        lhs: String::from("bar_key"),
+                        modes: vec![String::from("n")],
+                        desc: None,
+                    }],
                     lazy: true,
                 },
                 input::Bundle {
@@ -710,12 +1573,18 @@ mod tests {
                 id: "foo",
                 plugin: "foo_plugin",
                 startup: "foo startup",
+                priority: 10,
+                before: vec!["bar"],
+                after: vec![],
             }],
             lazy_plugins: vec![
                 LazyPlugin {
                     id: "bar",
                     plugin: "bar_plugin",
                     startup: "bar startup",
+                    priority: 0,
+                    before: vec![],
+                    after: vec!["foo"],
                     pre_config: "bar pre config",
                     config: "bar config",
                     deps: vec!["baz"],
@@ -743,6 +1612,9 @@ mod tests {
                     id: "hoge",
                     plugins: vec!["bar", "qux"],
                     startup: "hoge startup",
+                    priority: -1,
+                    before: vec![],
+                    after: vec![],
                     pre_config: "hoge pre config",
                     config: "hoge config",
                     deps: vec!["quux"],
@@ -758,14 +1630,39 @@ mod tests {
                 },
             ],
             cmds: vec![],
+            keys: vec![
+                Key {
+                    mode: "n",
+                    lhs: "bar_key",
+                    desc: Some("bar desc"),
+                },
+                Key {
+                    mode: "x",
+                    lhs: "bar_key",
+                    desc: Some("bar desc"),
+                },
+                Key {
+                    mode: "n",
+                    lhs: "bar_key",
+                    desc: None,
+                },
+            ],
             mods: vec![],
             evs: vec![],
             fts: vec![],
-            cmd_map: HashMap::from([("bar_cmd", vec!["bar"]), ("hoge_cmd", vec!["hoge"])]),
-            mod_map: HashMap::from([("bar_mod", vec!["bar"]), ("hoge_mod", vec!["hoge"])]),
-            ev_map: HashMap::from([("bar_ev", vec!["bar"]), ("hoge_ev", vec!["hoge"])]),
-            ft_map: HashMap::from([("bar_ft", vec!["bar"]), ("hoge_ft", vec!["hoge"])]),
+            cmd_map: BTreeMap::from([("bar_cmd", vec!["bar"]), ("hoge_cmd", vec!["hoge"])]),
+            mod_map: BTreeMap::from([("bar_mod", vec!["bar"]), ("hoge_mod", vec!["hoge"])]),
+            ev_map: BTreeMap::from([
+                (("bar_ev", "*"), vec!["bar"]),
+                (("hoge_ev", "*.rs"), vec!["hoge"]),
+            ]),
+            ft_map: BTreeMap::from([("bar_ft", vec!["bar"]), ("hoge_ft", vec!["hoge"])]),
+            key_map: BTreeMap::from([
+                (("n", "bar_key"), vec!["bar", "hoge"]),
+                (("x", "bar_key"), vec!["bar"]),
+            ]),
             lazys: vec!["bar", "hoge"],
+            startups: vec![],
         };
 
         // act:
@@ -774,4 +1671,225 @@ mod tests {
         // assert:
         assert_eq!(act, exp);
     }
+
+    /// start plugin `id` with startup `id`, ordered by `priority`, `before` and `after`.
+    fn ordered<'a>(
+        id: &'a str,
+        priority: i64,
+        before: Vec<&'a str>,
+        after: Vec<&'a str>,
+    ) -> StartupPlugin<'a> {
+        StartupPlugin {
+            id,
+            startup: id,
+            priority,
+            before,
+            after,
+            ..Default::default()
+        }
+    }
+
+    #[rstest(arg, exp,
+        case(
+            vec![ordered("a", 0, vec![], vec![]), ordered("b", 0, vec![], vec![])],
+            vec!["a", "b"]
+        ),
+        case(
+            vec![ordered("a", 0, vec![], vec![]), ordered("b", 10, vec![], vec![])],
+            vec!["b", "a"]
+        ),
+        case(
+            vec![ordered("a", -1, vec![], vec![]), ordered("b", 0, vec![], vec![])],
+            vec!["b", "a"]
+        ),
+        case(
+            vec![ordered("a", 0, vec![], vec!["b"]), ordered("b", 0, vec![], vec![])],
+            vec!["b", "a"]
+        ),
+        case(
+            vec![
+                ordered("a", 0, vec![], vec![]),
+                ordered("b", 0, vec!["a"], vec![]),
+                ordered("c", 10, vec![], vec![]),
+            ],
+            vec!["c", "b", "a"]
+        ),
+        // `before` wins over `priority`.
+        case(
+            vec![ordered("a", 10, vec![], vec!["b"]), ordered("b", 0, vec![], vec![])],
+            vec!["b", "a"]
+        ),
+        // a snippet without startup code can still be used as an anchor.
+        case(
+            vec![
+                ordered("a", 0, vec![], vec!["b"]),
+                StartupPlugin { id: "b", after: vec!["c"], ..Default::default() },
+                ordered("c", -1, vec![], vec![]),
+            ],
+            vec!["c", "a"]
+        ),
+    )]
+    fn order_startup(arg: Vec<StartupPlugin>, exp: Vec<&str>) {
+        // arrange:
+        let config = OboroConfig {
+            startup_plugins: arg,
+            ..Default::default()
+        };
+
+        // act:
+        let act = order_startups(&config).unwrap();
+
+        // assert:
+        assert_eq!(act.into_iter().map(|(id, _)| id).collect::<Vec<_>>(), exp);
+    }
+
+    #[rstest(arg, exp,
+        case(
+            vec![ordered("a", 0, vec!["b"], vec!["b"]), ordered("b", 0, vec![], vec![])],
+            "contradictory startup order: a -> b -> a."
+        ),
+        case(
+            vec![
+                ordered("a", 0, vec!["b"], vec![]),
+                ordered("b", 0, vec!["c"], vec![]),
+                ordered("c", 0, vec!["a"], vec![]),
+            ],
+            "contradictory startup order: a -> b -> c -> a."
+        ),
+        case(
+            vec![ordered("a", 0, vec![], vec!["unknown"])],
+            "`a` is ordered after unknown id `unknown`."
+        ),
+    )]
+    fn order_startup_error(arg: Vec<StartupPlugin>, exp: &str) {
+        // arrange:
+        let config = OboroConfig {
+            startup_plugins: arg,
+            ..Default::default()
+        };
+
+        // act:
+        let act = order_startups(&config);
+
+        // assert:
+        assert_eq!(act.unwrap_err().to_string(), exp);
+    }
+
+    #[test]
+    fn resolve_keys() {
+        // arrange:
+        let key = |lhs: &str, desc: Option<&str>| input::Key {
+            lhs: String::from(lhs),
+            modes: vec![String::from("n")],
+            desc: desc.map(String::from),
+        };
+        let src = input::OboroPluginConfig {
+            start_plugins: vec![],
+            opt_plugins: vec![
+                input::OptPlugin {
+                    id: String::from("foo"),
+                    keys: vec![key("<leader>b", None), key("<leader>a", None)],
+                    ..Default::default()
+                },
+                input::OptPlugin {
+                    id: String::from("bar"),
+                    keys: vec![key("<leader>b", Some("bar desc"))],
+                    ..Default::default()
+                },
+            ],
+            bundles: vec![],
+        };
+
+        // act:
+        let act = resolve(&src).unwrap();
+
+        // assert:
+        assert_eq!(
+            act.keys,
+            vec![
+                Key {
+                    mode: "n",
+                    lhs: "<leader>a",
+                    desc: None,
+                },
+                Key {
+                    mode: "n",
+                    lhs: "<leader>b",
+                    desc: Some("bar desc"),
+                },
+            ]
+        );
+        assert_eq!(act.key_map[&("n", "<leader>b")], vec!["foo", "bar"]);
+    }
+
+    #[rstest(arg_x, arg_y, exp,
+        case("foo", "bar", "first difference at line 1 (1 vs 1 lines):\n  - `foo`\n  + `bar`"),
+        case("a\nb\nc", "a\nx\nc\nd", "first difference at line 2 (3 vs 4 lines):\n  - `b`\n  + `x`"),
+        case("a", "a\nb", "first difference at line 2 (1 vs 2 lines):\n  - <none>\n  + `b`"),
+        case(&"x".repeat(61), "y", &format!("first difference at line 1 (1 vs 1 lines):\n  - `{}...`\n  + `y`", "x".repeat(60))),
+        // differences only in line breaks are shown escaped.
+        case("a\n", "a", "first difference at line 1 (1 vs 1 lines):\n  - `a\\n`\n  + `a`"),
+        case("a\r\nb", "a\nb", "first difference at line 1 (2 vs 2 lines):\n  - `a\\r`\n  + `a`"),
+        case("a\tb", "a  b", "first difference at line 1 (1 vs 1 lines):\n  - `a\\tb`\n  + `a  b`"),
+    )]
+    fn summarize_diff(arg_x: &str, arg_y: &str, exp: &str) {
+        // act:
+        let act = diff_summary(arg_x, arg_y);
+
+        // assert:
+        assert_eq!(act, exp);
+    }
+
+    #[test]
+    fn merge_error_has_context() {
+        // arrange:
+        let mut x = LazyPlugin {
+            id: "foo",
+            config: "local a = 1\nsetup(a)",
+            ..Default::default()
+        };
+        let mut y = LazyPlugin {
+            id: "foo",
+            config: "local a = 1\nsetup(b)",
+            ..Default::default()
+        };
+
+        // act:
+        let act = x.merge_into(&mut y).unwrap_err();
+
+        // assert:
+        assert_eq!(
+            format!("{:#}", act),
+            "Conflicted `config` of opt plugin `foo`: first difference at line 2 (2 vs 2 lines):\n  \
+             - `setup(a)`\n  + `setup(b)`"
+        );
+    }
+
+    #[test]
+    fn syntax_error_has_context() {
+        // arrange:
+        let config = OboroConfig {
+            startup_plugins: vec![StartupPlugin {
+                id: "foo",
+                startup: "vim.g.foo = 1",
+                ..Default::default()
+            }],
+            bundles: vec![Bundle {
+                id: "bar",
+                config: "if vim.g.foo then\n  setup()\n",
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        // act:
+        let act = validate_syntax(&config).unwrap_err();
+
+        // assert:
+        assert_eq!(
+            format!("{:#}", act),
+            "Invalid lua in `config` of bundle `bar`: line 3, column 1: \
+             'end' expected (to close 'if' at line 1) near '<eof>'"
+        );
+    }
 }
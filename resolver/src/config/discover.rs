@@ -0,0 +1,490 @@
+use crate::config::input::{Bundle, OboroPluginConfig, OptPlugin};
+use anyhow::{Context, Result};
+use log::{debug, warn};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// whether `dir` contains a lua file (at any depth).
+fn has_lua(dir: &Path) -> Result<bool> {
+    for entry in fs::read_dir(dir).with_context(|| format!("failed to read `{}`", dir.display()))? {
+        let path = entry?.path();
+        if path.is_dir() {
+            if has_lua(&path)? {
+                return Ok(true);
+            }
+        } else if path.extension().is_some_and(|x| x == "lua") {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// top-level modules provided by the plugin at `root` (`lua/<MODULE>.lua` or `lua/<MODULE>/`).
+pub fn provided_modules(root: &Path) -> Result<BTreeSet<String>> {
+    let lua = root.join("lua");
+    let entries = match fs::read_dir(&lua) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(BTreeSet::new()),
+        Err(err) => return Err(err).with_context(|| format!("failed to read `{}`", lua.display())),
+    };
+    let mut modules = BTreeSet::new();
+    for entry in entries {
+        let path = entry?.path();
+        let name = if path.is_dir() {
+            if !has_lua(&path)? {
+                continue;
+            }
+            path.file_name()
+        } else if path.extension().is_some_and(|x| x == "lua") {
+            path.file_stem()
+        } else {
+            continue;
+        };
+        // `require` can not reach names containing `.`.
+        match name.and_then(|x| x.to_str()) {
+            Some(name) if !name.contains('.') => {
+                modules.insert(String::from(name));
+            }
+            _ => (),
+        }
+    }
+    Ok(modules)
+}
+
+/// lua and vim script files under `dir` (recursively, missing `dir` is empty).
+fn sources(dir: &Path) -> Result<Vec<PathBuf>> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err).with_context(|| format!("failed to read `{}`", dir.display())),
+    };
+    let mut files = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        if path.is_dir() {
+            files.extend(sources(&path)?);
+        } else if path.extension().is_some_and(|x| x == "lua" || x == "vim") {
+            files.push(path);
+        }
+    }
+    Ok(files)
+}
+
+/// user command name at the start of `s` (e.g. `Foo` of `Foo call foo()` or `Foo')`).
+///
+/// the name must end at whitespace, a quote or `]` (of `[[...]]`), so `Foo-bar` is not a name.
+fn command_name(s: &str) -> Option<&str> {
+    let end = s
+        .find(|c: char| !c.is_ascii_alphanumeric())
+        .unwrap_or(s.len());
+    let (name, rest) = s.split_at(end);
+    (name.starts_with(|c: char| c.is_ascii_uppercase())
+        && (rest.is_empty() || rest.starts_with(|c: char| c.is_whitespace() || "'\"]".contains(c))))
+    .then_some(name)
+}
+
+/// logical lines of `code`, joining vim script `\` continuation lines (and skipping `"\ ` comments).
+fn logical_lines(code: &str, is_vim: bool) -> Vec<String> {
+    let mut lines = Vec::<String>::new();
+    for line in code.lines() {
+        let trimmed = line.trim_start();
+        if is_vim && trimmed.starts_with("\"\\ ") {
+            continue;
+        }
+        match (is_vim, trimmed.strip_prefix('\\'), lines.last_mut()) {
+            (true, Some(rest), Some(last)) => last.push_str(rest),
+            _ => lines.push(String::from(trimmed)),
+        }
+    }
+    lines
+}
+
+/// user commands defined in `code` by `:command` or `nvim_create_user_command`.
+///
+/// best-effort: commands defined with a computed name (e.g. `exe 'command ' .. name`) are not found.
+fn defined_commands(code: &str, is_vim: bool) -> Vec<String> {
+    let mut commands = Vec::new();
+    for line in logical_lines(code, is_vim) {
+        let line = line.as_str();
+        if line.starts_with(if is_vim { "\"" } else { "--" }) {
+            continue;
+        }
+        // `:com[mand][!] [-attr]... {name} {repl}` (also inside `vim.cmd`).
+        for (i, _) in line.match_indices("com") {
+            let prev = line[..i].chars().next_back();
+            if prev.is_some_and(|c| c.is_ascii_alphanumeric() || c == '_') {
+                continue;
+            }
+            let word_len = line[i..]
+                .find(|c: char| !c.is_ascii_alphabetic())
+                .unwrap_or(line.len() - i);
+            if !"command".starts_with(&line[i..i + word_len]) {
+                continue;
+            }
+            let rest = &line[i + word_len..];
+            let bang = rest.starts_with('!');
+            // e.g. `vim.cmd("command Foo ...")` or `vim.cmd [[command Foo ...]]`.
+            let string_start = matches!(prev, Some('"' | '\'' | '['));
+            // elsewhere than the start of a vim script line or a string, `command` may be a plain word.
+            if !(is_vim && i == 0 || bang || string_start) {
+                continue;
+            }
+            let rest = rest.trim_start_matches('!');
+            if !rest.starts_with(char::is_whitespace) {
+                continue;
+            }
+            let name = rest
+                .split_whitespace()
+                .find(|x| !x.starts_with('-'))
+                .and_then(command_name);
+            commands.extend(name.map(String::from));
+        }
+        // `vim.cmd.com[mand]({ "-attr", "{name}", "{repl}" })` (the first string not being an attribute).
+        for (i, _) in line.match_indices("cmd.com") {
+            let rest = &line[i + "cmd.".len()..];
+            let word_len = rest
+                .find(|c: char| !c.is_ascii_alphabetic())
+                .unwrap_or(rest.len());
+            if !"command".starts_with(&rest[..word_len]) {
+                continue;
+            }
+            let mut rest = &rest[word_len..];
+            let name = loop {
+                let Some(start) = rest.find(['"', '\'']) else {
+                    break None;
+                };
+                let quote = &rest[start..start + 1];
+                let Some(len) = rest[start + 1..].find(quote) else {
+                    break None;
+                };
+                let arg = &rest[start + 1..start + 1 + len];
+                rest = &rest[start + 2 + len..];
+                if !arg.starts_with('-') {
+                    break command_name(arg);
+                }
+            };
+            commands.extend(name.map(String::from));
+        }
+        // `nvim_create_user_command("{name}", ...)`
+        for (i, _) in line.match_indices("nvim_create_user_command") {
+            let rest = line[i + "nvim_create_user_command".len()..].trim_start();
+            let rest = rest.strip_prefix('(').unwrap_or(rest).trim_start();
+            let name = rest
+                .strip_prefix(['"', '\''])
+                .and_then(command_name)
+                .filter(|x| rest[1 + x.len()..].starts_with(&rest[..1]));
+            commands.extend(name.map(String::from));
+        }
+    }
+    commands
+}
+
+/// user commands defined by the plugin at `root` (in `plugin/` and `lua/`).
+pub fn provided_commands(root: &Path) -> Result<BTreeSet<String>> {
+    let mut commands = BTreeSet::new();
+    for path in sources(&root.join("plugin"))?
+        .into_iter()
+        .chain(sources(&root.join("lua"))?)
+    {
+        let bytes =
+            fs::read(&path).with_context(|| format!("failed to read `{}`", path.display()))?;
+        let code = String::from_utf8_lossy(&bytes);
+        let is_vim = path.extension().is_some_and(|x| x == "vim");
+        commands.extend(defined_commands(&code, is_vim));
+    }
+    Ok(commands)
+}
+
+fn quote_all<'a>(xs: impl IntoIterator<Item = &'a String>) -> String {
+    xs.into_iter()
+        .map(|x| format!("`{}`", x))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// add names found by `scan` in each opt plugin to its triggers (`field`).
+///
+/// names provided by several plugins, or declared by another plugin (or bundle), are reported and skipped.
+fn discover(
+    config: &mut OboroPluginConfig,
+    kind: &str,
+    scan: fn(&Path) -> Result<BTreeSet<String>>,
+    field: fn(&mut OptPlugin) -> &mut Vec<String>,
+    bundle_field: fn(&Bundle) -> &Vec<String>,
+) -> Result<BTreeMap<String, BTreeSet<String>>> {
+    // name -> ids declaring it.
+    let mut declared = BTreeMap::<String, BTreeSet<String>>::new();
+    for plugin in config.opt_plugins.iter_mut() {
+        let id = plugin.id.clone();
+        for name in field(plugin).iter() {
+            declared.entry(name.clone()).or_default().insert(id.clone());
+        }
+    }
+    for bundle in config.bundles.iter() {
+        for name in bundle_field(bundle).iter() {
+            declared
+                .entry(name.clone())
+                .or_default()
+                .insert(bundle.id.clone());
+        }
+    }
+
+    // id -> names provided by it.
+    let mut scanned = BTreeMap::<String, BTreeSet<String>>::new();
+    for plugin in config.opt_plugins.iter() {
+        if !scanned.contains_key(&plugin.id) {
+            scanned.insert(plugin.id.clone(), scan(Path::new(&plugin.plugin))?);
+        }
+    }
+    // name -> ids providing it.
+    let mut provided = BTreeMap::<&str, BTreeSet<String>>::new();
+    for (id, names) in scanned.iter() {
+        for name in names.iter() {
+            provided.entry(name).or_default().insert(id.clone());
+        }
+    }
+
+    for (name, ids) in provided {
+        if ids.len() > 1 {
+            warn!(
+                "{} `{}` is provided by {}; declare it explicitly.",
+                kind,
+                name,
+                quote_all(&ids)
+            );
+            continue;
+        }
+        let id = ids.first().unwrap();
+        match declared.get(name) {
+            Some(owners) if owners.contains(id) => continue,
+            Some(owners) => {
+                warn!(
+                    "{} `{}` is provided by `{}` but declared by {}; skipped.",
+                    kind,
+                    name,
+                    id,
+                    quote_all(owners)
+                );
+                continue;
+            }
+            None => (),
+        }
+        // the first entry is enough as entries of the same id are merged.
+        if let Some(plugin) = config.opt_plugins.iter_mut().find(|x| &x.id == id) {
+            debug!(id = id, kind = kind, name = name; "discover");
+            field(plugin).push(String::from(name));
+        }
+    }
+    Ok(scanned)
+}
+
+/// add top-level modules found in `lua/` of each opt plugin to its module triggers.
+///
+/// modules provided by several plugins, or declared by another plugin, are reported and skipped.
+pub fn discover_modules(config: &mut OboroPluginConfig) -> Result<()> {
+    discover(
+        config,
+        "module",
+        provided_modules,
+        |x| &mut x.mods,
+        |x| &x.mods,
+    )?;
+    Ok(())
+}
+
+/// add user commands defined by each opt plugin to its command triggers,
+/// and report declared commands the plugin does not define.
+pub fn discover_commands(config: &mut OboroPluginConfig) -> Result<()> {
+    let scanned = discover(
+        config,
+        "command",
+        provided_commands,
+        |x| &mut x.cmds,
+        |x| &x.cmds,
+    )?;
+    for plugin in config.opt_plugins.iter() {
+        let defined = &scanned[&plugin.id];
+        // nothing to compare with (e.g. commands defined at runtime only).
+        if defined.is_empty() {
+            continue;
+        }
+        for cmd in plugin.cmds.iter().filter(|x| !defined.contains(*x)) {
+            warn!(
+                "`{}` declares command `{}`, but does not define it.",
+                plugin.id, cmd
+            );
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use tempfile::{tempdir, TempDir};
+
+    /// plugin directory containing `files`.
+    fn plugin(files: &[&str]) -> TempDir {
+        let dir = tempdir().unwrap();
+        for file in files {
+            let path = dir.path().join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "").unwrap();
+        }
+        dir
+    }
+
+    fn opt(id: &str, root: &Path, mods: &[&str]) -> OptPlugin {
+        OptPlugin {
+            id: String::from(id),
+            plugin: root.to_string_lossy().into_owned(),
+            mods: mods.iter().map(|x| String::from(*x)).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn provided_modules_of_plugin() {
+        // arrange:
+        let dir = plugin(&[
+            "lua/foo/init.lua",
+            "lua/foo/bar.lua",
+            "lua/baz.lua",
+            "lua/deep/a/b.lua",
+            "lua/empty/README.md",
+            "lua/foo.bar.lua",
+            "lua/README.md",
+            "plugin/foo.vim",
+        ]);
+
+        // act:
+        let act = provided_modules(dir.path()).unwrap();
+
+        // assert:
+        assert_eq!(
+            act.iter().map(|x| x.as_str()).collect::<Vec<_>>(),
+            vec!["baz", "deep", "foo"]
+        );
+    }
+
+    #[test]
+    fn provided_modules_without_lua() {
+        // arrange:
+        let dir = plugin(&["plugin/foo.vim"]);
+
+        // act:
+        let act = provided_modules(dir.path()).unwrap();
+
+        // assert:
+        assert!(act.is_empty());
+    }
+
+    #[test]
+    fn discover() {
+        // arrange:
+        let foo = plugin(&["lua/foo/init.lua", "lua/shared.lua", "lua/taken.lua"]);
+        let bar = plugin(&["lua/bar.lua", "lua/shared/init.lua"]);
+        let baz = plugin(&["lua/baz.lua"]);
+        let mut config = OboroPluginConfig {
+            start_plugins: vec![],
+            opt_plugins: vec![
+                opt("foo", foo.path(), &[]),
+                opt("bar", bar.path(), &["bar", "taken"]),
+                opt("baz", baz.path(), &[]),
+                opt("foo", foo.path(), &[]),
+            ],
+            bundles: vec![],
+        };
+
+        // act:
+        discover_modules(&mut config).unwrap();
+
+        // assert:
+        let mods = config
+            .opt_plugins
+            .iter()
+            .map(|x| (x.id.as_str(), x.mods.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            mods,
+            vec![
+                ("foo", vec![String::from("foo")]),
+                ("bar", vec![String::from("bar"), String::from("taken")]),
+                ("baz", vec![String::from("baz")]),
+                ("foo", vec![]),
+            ]
+        );
+    }
+
+    #[rstest(code, is_vim, exp,
+        case("command! Foo call foo()", true, vec!["Foo"]),
+        case("command -nargs=* -bang FooBar call foo()", true, vec!["FooBar"]),
+        case("  com! -nargs=1 Short echo", true, vec!["Short"]),
+        case("comm Abbr echo", true, vec!["Abbr"]),
+        case("vim.cmd('com! Abbr echo')", false, vec!["Abbr"]),
+        case("comment Foo", true, vec![]),
+        case("command! -nargs=1\n      \\ -complete=file\n      \"\\ a comment\n      \\ Cont call foo()", true, vec!["Cont"]),
+        case("vim.cmd('command! Quoted')", false, vec!["Quoted"]),
+        case("vim.cmd(\"command -nargs=1 NoBang echo\")", false, vec!["NoBang"]),
+        case("vim.cmd [[com NoBang echo]]", false, vec!["NoBang"]),
+        case("local command = 'Foo'", false, vec![]),
+        case("vim.cmd.command({ '-nargs=1', 'Table', 'echo' })", false, vec!["Table"]),
+        case("vim.cmd.command('Args', 'echo')", false, vec!["Args"]),
+        case("vim.cmd.com { args = { \"Short\", \"echo\" }, bang = true }", false, vec!["Short"]),
+        case("vim.cmd.command('Foo-bar', 'echo')", false, vec![]),
+        case("vim.cmd.comclear()", false, vec![]),
+        case("\" command! Commented", true, vec![]),
+        case("-- vim.cmd('command! Commented')", false, vec![]),
+        case("command! foo call foo()", true, vec![]),
+        case("command Foo echo", false, vec![]),
+        case("vim.cmd([[command! -range Bar lua bar()]])", false, vec!["Bar"]),
+        case("vim.api.nvim_create_user_command('Baz', baz, {})", false, vec!["Baz"]),
+        case("vim.api.nvim_create_user_command( \"Qux\", qux, {})", false, vec!["Qux"]),
+        case("vim.api.nvim_create_user_command(name, fn, {})", false, vec![]),
+        case("vim.api.nvim_create_user_command('Foo-bar', fn, {})", false, vec![]),
+        case("vim.api.nvim_create_user_command('Foo\", fn, {})", false, vec![]),
+        case("command! Foo-bar echo", true, vec![]),
+        case("vim.api.nvim_buf_create_user_command(0, 'Buf', fn, {})", false, vec![]),
+        case("let s:subcommand! = 1", true, vec![]),
+    )]
+    fn commands_in_code(code: &str, is_vim: bool, exp: Vec<&str>) {
+        // act:
+        let act = defined_commands(code, is_vim);
+
+        // assert:
+        assert_eq!(act, exp);
+    }
+
+    #[test]
+    fn discover_command() {
+        // arrange:
+        let foo = plugin(&["plugin/foo.vim", "lua/foo/init.lua"]);
+        fs::write(
+            foo.path().join("plugin/foo.vim"),
+            "command! Foo call foo#run()\n",
+        )
+        .unwrap();
+        fs::write(
+            foo.path().join("lua/foo/init.lua"),
+            "vim.api.nvim_create_user_command('FooToggle', toggle, {})\n",
+        )
+        .unwrap();
+        let mut config = OboroPluginConfig {
+            start_plugins: vec![],
+            opt_plugins: vec![OptPlugin {
+                cmds: vec![String::from("FooToggle"), String::from("Gone")],
+                ..opt("foo", foo.path(), &[])
+            }],
+            bundles: vec![],
+        };
+
+        // act:
+        discover_commands(&mut config).unwrap();
+
+        // assert:
+        assert_eq!(config.opt_plugins[0].cmds, vec!["FooToggle", "Gone", "Foo"]);
+    }
+}
@@ -0,0 +1,139 @@
+/// autocmd events of neovim (`:help autocmd-events`), including aliases and events of newer releases.
+pub const EVENTS: &[&str] = &[
+    "BufAdd",
+    "BufCreate",
+    "BufDelete",
+    "BufEnter",
+    "BufFilePost",
+    "BufFilePre",
+    "BufHidden",
+    "BufLeave",
+    "BufModifiedSet",
+    "BufNew",
+    "BufNewFile",
+    "BufRead",
+    "BufReadCmd",
+    "BufReadPost",
+    "BufReadPre",
+    "BufUnload",
+    "BufWinEnter",
+    "BufWinLeave",
+    "BufWipeout",
+    "BufWrite",
+    "BufWriteCmd",
+    "BufWritePost",
+    "BufWritePre",
+    "ChanInfo",
+    "ChanOpen",
+    "CmdUndefined",
+    "CmdWinEnter",
+    "CmdWinLeave",
+    "CmdlineChanged",
+    "CmdlineEnter",
+    "CmdlineLeave",
+    "ColorScheme",
+    "ColorSchemePre",
+    "CompleteChanged",
+    "CompleteDone",
+    "CompleteDonePre",
+    "CursorHold",
+    "CursorHoldI",
+    "CursorMoved",
+    "CursorMovedC",
+    "CursorMovedI",
+    "DiagnosticChanged",
+    "DiffUpdated",
+    "DirChanged",
+    "DirChangedPre",
+    "EncodingChanged",
+    "ExitPre",
+    "FileAppendCmd",
+    "FileAppendPost",
+    "FileAppendPre",
+    "FileChangedRO",
+    "FileChangedShell",
+    "FileChangedShellPost",
+    "FileEncoding",
+    "FileReadCmd",
+    "FileReadPost",
+    "FileReadPre",
+    "FileType",
+    "FileWriteCmd",
+    "FileWritePost",
+    "FileWritePre",
+    "FilterReadPost",
+    "FilterReadPre",
+    "FilterWritePost",
+    "FilterWritePre",
+    "FocusGained",
+    "FocusLost",
+    "FuncUndefined",
+    "InsertChange",
+    "InsertCharPre",
+    "InsertEnter",
+    "InsertLeave",
+    "InsertLeavePre",
+    "LspAttach",
+    "LspDetach",
+    "LspNotify",
+    "LspProgress",
+    "LspRequest",
+    "LspTokenUpdate",
+    "MenuPopup",
+    "ModeChanged",
+    "OptionSet",
+    "Progress",
+    "QuickFixCmdPost",
+    "QuickFixCmdPre",
+    "QuitPre",
+    "RecordingEnter",
+    "RecordingLeave",
+    "RemoteReply",
+    "SafeState",
+    "SessionLoadPost",
+    "SessionWritePost",
+    "ShellCmdPost",
+    "ShellFilterPost",
+    "Signal",
+    "SourceCmd",
+    "SourcePost",
+    "SourcePre",
+    "SpellFileMissing",
+    "StdinReadPost",
+    "StdinReadPre",
+    "SwapExists",
+    "Syntax",
+    "TabClosed",
+    "TabClosedPre",
+    "TabEnter",
+    "TabLeave",
+    "TabNew",
+    "TabNewEntered",
+    "TermChanged",
+    "TermClose",
+    "TermEnter",
+    "TermLeave",
+    "TermOpen",
+    "TermRequest",
+    "TermResponse",
+    "TextChanged",
+    "TextChangedI",
+    "TextChangedP",
+    "TextChangedT",
+    "TextYankPost",
+    "UIEnter",
+    "UILeave",
+    "User",
+    "VimEnter",
+    "VimLeave",
+    "VimLeavePre",
+    "VimResized",
+    "VimResume",
+    "VimSuspend",
+    "WinClosed",
+    "WinEnter",
+    "WinLeave",
+    "WinNew",
+    "WinResized",
+    "WinScrolled",
+];
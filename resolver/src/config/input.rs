@@ -1,5 +1,27 @@
 use serde::Deserialize;
 
+/// key mapping.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
+pub struct Key {
+    /// left-hand side of the mapping.
+    pub lhs: String,
+    /// modes of the mapping (e.g. `n`, `x`).
+    pub modes: Vec<String>,
+    /// description of the mapping.
+    pub desc: Option<String>,
+}
+
+/// event trigger.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
+pub struct Event {
+    /// autocmd event (e.g. `BufReadPre`, `User`).
+    pub event: String,
+    /// autocmd pattern (`*` if not set).
+    pub pattern: Option<String>,
+}
+
 /// `start` plugin.
 #[derive(Debug, Default, Deserialize)]
 #[serde(deny_unknown_fields, rename_all = "camelCase")]
@@ -8,6 +30,12 @@ pub struct StartPlugin {
     pub plugin: String,
     /// lua code execute at startup.
     pub startup: String,
+    /// startup order (higher runs first).
+    pub priority: i64,
+    /// ids whose startup runs after this one.
+    pub before: Vec<String>,
+    /// ids whose startup runs before this one.
+    pub after: Vec<String>,
 }
 
 /// `opt` plugin.
@@ -18,6 +46,12 @@ pub struct OptPlugin {
     pub plugin: String,
     /// lua code execute at startup.
     pub startup: String,
+    /// startup order (higher runs first).
+    pub priority: i64,
+    /// ids whose startup runs after this one.
+    pub before: Vec<String>,
+    /// ids whose startup runs before this one.
+    pub after: Vec<String>,
     /// lua code execute before load.
     pub pre_config: String,
     /// lua code execute on load.
@@ -29,11 +63,13 @@ pub struct OptPlugin {
     /// load on modules.
     pub mods: Vec<String>,
     /// load on events.
-    pub evs: Vec<String>,
+    pub evs: Vec<Event>,
     /// load on filetypes.
     pub fts: Vec<String>,
     /// load on commands.
     pub cmds: Vec<String>,
+    /// load on key mappings.
+    pub keys: Vec<Key>,
     /// load lazy.
     pub lazy: bool,
 }
@@ -46,6 +82,12 @@ pub struct Bundle {
     pub plugins: Vec<String>,
     /// lua code execute at startup.
     pub startup: String,
+    /// startup order (higher runs first).
+    pub priority: i64,
+    /// ids whose startup runs after this one.
+    pub before: Vec<String>,
+    /// ids whose startup runs before this one.
+    pub after: Vec<String>,
     /// lua code execute before load.
     pub pre_config: String,
     /// lua code execute on load.
@@ -57,11 +99,13 @@ pub struct Bundle {
     /// load on modules.
     pub mods: Vec<String>,
     /// load on events.
-    pub evs: Vec<String>,
+    pub evs: Vec<Event>,
     /// load on filetypes.
     pub fts: Vec<String>,
     /// load on commands.
     pub cmds: Vec<String>,
+    /// load on key mappings.
+    pub keys: Vec<Key>,
     /// load lazy.
     pub lazy: bool,
 }
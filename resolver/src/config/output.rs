@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
 /// plugin loaded when startup vim.
 #[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord)]
@@ -6,6 +6,9 @@ pub struct StartupPlugin<'a> {
     pub id: &'a str,
     pub plugin: &'a str,
     pub startup: &'a str,
+    pub priority: i64,
+    pub before: Vec<&'a str>,
+    pub after: Vec<&'a str>,
 }
 
 /// plugin loaded on demand.
@@ -14,6 +17,9 @@ pub struct LazyPlugin<'a> {
     pub id: &'a str,
     pub plugin: &'a str,
     pub startup: &'a str,
+    pub priority: i64,
+    pub before: Vec<&'a str>,
+    pub after: Vec<&'a str>,
     pub pre_config: &'a str,
     pub config: &'a str,
     pub deps: Vec<&'a str>,
@@ -26,12 +32,23 @@ pub struct Bundle<'a> {
     pub id: &'a str,
     pub plugins: Vec<&'a str>,
     pub startup: &'a str,
+    pub priority: i64,
+    pub before: Vec<&'a str>,
+    pub after: Vec<&'a str>,
     pub pre_config: &'a str,
     pub config: &'a str,
     pub deps: Vec<&'a str>,
     pub dep_bundles: Vec<&'a str>,
 }
 
+/// key mapping trigger.
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Key<'a> {
+    pub mode: &'a str,
+    pub lhs: &'a str,
+    pub desc: Option<&'a str>,
+}
+
 /// oboro config.
 #[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct OboroConfig<'a> {
@@ -39,12 +56,19 @@ pub struct OboroConfig<'a> {
     pub lazy_plugins: Vec<LazyPlugin<'a>>,
     pub bundles: Vec<Bundle<'a>>,
     pub mods: Vec<&'a str>,
-    pub evs: Vec<&'a str>,
+    /// (event, pattern).
+    pub evs: Vec<(&'a str, &'a str)>,
     pub fts: Vec<&'a str>,
     pub cmds: Vec<&'a str>,
-    pub mod_map: HashMap<&'a str, Vec<&'a str>>,
-    pub ev_map: HashMap<&'a str, Vec<&'a str>>,
-    pub ft_map: HashMap<&'a str, Vec<&'a str>>,
-    pub cmd_map: HashMap<&'a str, Vec<&'a str>>,
+    pub keys: Vec<Key<'a>>,
+    pub mod_map: BTreeMap<&'a str, Vec<&'a str>>,
+    /// (event, pattern) -> ids.
+    pub ev_map: BTreeMap<(&'a str, &'a str), Vec<&'a str>>,
+    pub ft_map: BTreeMap<&'a str, Vec<&'a str>>,
+    pub cmd_map: BTreeMap<&'a str, Vec<&'a str>>,
+    /// (mode, lhs) -> ids.
+    pub key_map: BTreeMap<(&'a str, &'a str), Vec<&'a str>>,
     pub lazys: Vec<&'a str>,
+    /// (id, startup) in execution order.
+    pub startups: Vec<(&'a str, &'a str)>,
 }
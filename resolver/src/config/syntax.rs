@@ -0,0 +1,921 @@
+use crate::generator::lua::KEYWORDS;
+use std::fmt;
+
+/// symbols (longest first).
+const SYMBOLS: [&str; 23] = [
+    "...", "..", "==", "~=", "<=", ">=", "::", "+", "-", "*", "/", "%", "^", "#", "<", ">", "=",
+    "(", ")", "{", "}", "[", "]",
+];
+
+/// binary operators.
+const BINARY_OPS: [&str; 15] = [
+    "+", "-", "*", "/", "%", "^", "..", "==", "~=", "<", "<=", ">", ">=", "and", "or",
+];
+
+/// syntax error of lua code.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyntaxError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for SyntaxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+impl std::error::Error for SyntaxError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Name,
+    Keyword,
+    Number,
+    Str,
+    Symbol,
+    Eof,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Token<'a> {
+    kind: Kind,
+    text: &'a str,
+    line: usize,
+    column: usize,
+}
+
+impl Token<'_> {
+    fn is(&self, s: &str) -> bool {
+        matches!(self.kind, Kind::Keyword | Kind::Symbol) && self.text == s
+    }
+
+    /// token shown in error messages (e.g. `'end'`).
+    fn near(&self) -> String {
+        match self.kind {
+            Kind::Eof => String::from("'<eof>'"),
+            _ => format!("'{}'", self.text),
+        }
+    }
+}
+
+struct Lexer<'a> {
+    src: &'a str,
+    pos: usize,
+    line: usize,
+    line_start: usize,
+}
+
+impl<'a> Lexer<'a> {
+    fn column(&self) -> usize {
+        self.src[self.line_start..self.pos].chars().count() + 1
+    }
+
+    fn peek(&self, n: usize) -> Option<char> {
+        self.src[self.pos..].chars().nth(n)
+    }
+
+    /// consume a char (`\r\n` and `\n\r` are read as a single `\n`).
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek(0)?;
+        self.pos += c.len_utf8();
+        if c == '\n' || c == '\r' {
+            if let Some(d) = self.peek(0).filter(|&d| (d == '\n' || d == '\r') && d != c) {
+                self.pos += d.len_utf8();
+            }
+            self.line += 1;
+            self.line_start = self.pos;
+            return Some('\n');
+        }
+        Some(c)
+    }
+
+    fn error(&self, line: usize, column: usize, message: String) -> SyntaxError {
+        SyntaxError {
+            line,
+            column,
+            message,
+        }
+    }
+
+    /// level of the long bracket at the cursor (`[[` is 0, `[=[` is 1, ...).
+    fn long_bracket(&self) -> Option<usize> {
+        if self.peek(0) != Some('[') {
+            return None;
+        }
+        let level = self.src[self.pos + 1..]
+            .chars()
+            .take_while(|&c| c == '=')
+            .count();
+        (self.peek(level + 1) == Some('[')).then_some(level)
+    }
+
+    /// skip a long string (or comment) of `level`.
+    fn long_string(&mut self, level: usize, what: &str) -> Result<(), SyntaxError> {
+        let line = self.line;
+        for _ in 0..level + 2 {
+            self.bump();
+        }
+        loop {
+            match self.bump() {
+                None => {
+                    return Err(self.error(
+                        self.line,
+                        self.column(),
+                        format!(
+                            "unfinished long {} (starting at line {}) near '<eof>'",
+                            what, line
+                        ),
+                    ))
+                }
+                Some(']') => {
+                    let n = self.src[self.pos..]
+                        .chars()
+                        .take_while(|&c| c == '=')
+                        .count();
+                    if n == level && self.peek(n) == Some(']') {
+                        for _ in 0..n + 1 {
+                            self.bump();
+                        }
+                        return Ok(());
+                    }
+                }
+                Some(_) => {}
+            }
+        }
+    }
+
+    fn skip_whitespace_and_comments(&mut self) -> Result<(), SyntaxError> {
+        loop {
+            match self.peek(0) {
+                Some(c) if c.is_ascii_whitespace() || c == '\x0b' => {
+                    self.bump();
+                }
+                Some('-') if self.peek(1) == Some('-') => {
+                    self.bump();
+                    self.bump();
+                    if let Some(level) = self.long_bracket() {
+                        self.long_string(level, "comment")?;
+                    } else {
+                        while self.peek(0).is_some_and(|c| c != '\n' && c != '\r') {
+                            self.bump();
+                        }
+                    }
+                }
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    fn string(&mut self, start: usize, line: usize, column: usize) -> Result<(), SyntaxError> {
+        let quote = self.bump();
+        let unfinished = |lexer: &Self| {
+            lexer.error(
+                line,
+                column,
+                format!("unfinished string near '{}'", &lexer.src[start..lexer.pos]),
+            )
+        };
+        loop {
+            match self.peek(0) {
+                None | Some('\n') | Some('\r') => return Err(unfinished(self)),
+                c if c == quote => {
+                    self.bump();
+                    return Ok(());
+                }
+                Some('\\') => {
+                    self.bump();
+                    let valid = match self.bump() {
+                        None => return Err(unfinished(self)),
+                        Some(
+                            'a' | 'b' | 'f' | 'n' | 'r' | 't' | 'v' | '\\' | '"' | '\'' | '\n',
+                        ) => true,
+                        Some('x') => (0..2).all(|_| {
+                            self.peek(0).is_some_and(|c| c.is_ascii_hexdigit()) && {
+                                self.bump();
+                                true
+                            }
+                        }),
+                        Some('z') => {
+                            while self.peek(0).is_some_and(|c| c.is_ascii_whitespace()) {
+                                self.bump();
+                            }
+                            true
+                        }
+                        Some('u') => {
+                            let mut digits = String::new();
+                            let open = self.peek(0) == Some('{') && self.bump().is_some();
+                            while let Some(c) = self.peek(0).filter(|c| c.is_ascii_hexdigit()) {
+                                digits.push(c);
+                                self.bump();
+                            }
+                            open && self.peek(0) == Some('}') && self.bump().is_some() && {
+                                u32::from_str_radix(&digits, 16).is_ok_and(|n| n < 0x80000000)
+                            }
+                        }
+                        Some(c) if c.is_ascii_digit() => {
+                            let mut n = c.to_digit(10).unwrap_or_default();
+                            for _ in 0..2 {
+                                match self.peek(0).and_then(|c| c.to_digit(10)) {
+                                    Some(d) => {
+                                        n = n * 10 + d;
+                                        self.bump();
+                                    }
+                                    None => break,
+                                }
+                            }
+                            n <= 255
+                        }
+                        Some(_) => false,
+                    };
+                    if !valid {
+                        return Err(self.error(
+                            line,
+                            column,
+                            format!(
+                                "invalid escape sequence near '{}'",
+                                &self.src[start..self.pos]
+                            ),
+                        ));
+                    }
+                }
+                Some(_) => {
+                    self.bump();
+                }
+            }
+        }
+    }
+
+    fn number(&mut self, start: usize, line: usize, column: usize) -> Result<(), SyntaxError> {
+        // `e` is a hex digit, so only `p` marks the exponent of hex literals (`0xE+1` is `0xE + 1`).
+        let hex = matches!(self.peek(0), Some('0')) && matches!(self.peek(1), Some('x' | 'X'));
+        let mut prev = ' ';
+        while let Some(c) = self.peek(0) {
+            let exponent = if hex {
+                matches!(prev, 'p' | 'P')
+            } else {
+                matches!(prev, 'e' | 'E')
+            };
+            if c.is_ascii_alphanumeric()
+                || c == '_'
+                || c == '.'
+                || (exponent && (c == '+' || c == '-'))
+            {
+                self.bump();
+                prev = c;
+            } else {
+                break;
+            }
+        }
+        let text = &self.src[start..self.pos];
+        if is_number(text) {
+            Ok(())
+        } else {
+            Err(self.error(line, column, format!("malformed number near '{}'", text)))
+        }
+    }
+
+    fn next_token(&mut self) -> Result<Token<'a>, SyntaxError> {
+        self.skip_whitespace_and_comments()?;
+        let (start, line, column) = (self.pos, self.line, self.column());
+        let kind = match self.peek(0) {
+            None => Kind::Eof,
+            Some(c) if c.is_ascii_alphabetic() || c == '_' => {
+                while self
+                    .peek(0)
+                    .is_some_and(|c| c.is_ascii_alphanumeric() || c == '_')
+                {
+                    self.bump();
+                }
+                if KEYWORDS.contains(&&self.src[start..self.pos]) {
+                    Kind::Keyword
+                } else {
+                    Kind::Name
+                }
+            }
+            Some(c) if c.is_ascii_digit() => {
+                self.number(start, line, column)?;
+                Kind::Number
+            }
+            Some('.') if self.peek(1).is_some_and(|c| c.is_ascii_digit()) => {
+                self.number(start, line, column)?;
+                Kind::Number
+            }
+            Some('"' | '\'') => {
+                self.string(start, line, column)?;
+                Kind::Str
+            }
+            Some('[') if self.long_bracket().is_some() => {
+                self.long_string(self.long_bracket().unwrap_or_default(), "string")?;
+                Kind::Str
+            }
+            Some('[') if self.peek(1) == Some('=') => {
+                return Err(self.error(
+                    line,
+                    column,
+                    String::from("invalid long string delimiter near '[='"),
+                ));
+            }
+            Some(c) => {
+                let rest = &self.src[self.pos..];
+                let len = SYMBOLS
+                    .iter()
+                    .chain([";", ":", ",", "."].iter())
+                    .find(|s| rest.starts_with(**s))
+                    .map(|s| s.len())
+                    .unwrap_or(c.len_utf8());
+                self.pos += len;
+                Kind::Symbol
+            }
+        };
+        Ok(Token {
+            kind,
+            text: &self.src[start..self.pos],
+            line,
+            column,
+        })
+    }
+}
+
+/// whether `text` is a valid luajit number literal (e.g. `0x1p4`, `1e-3`, `1ULL`, `2i`).
+fn is_number(text: &str) -> bool {
+    let lower = text.to_ascii_lowercase();
+    let (body, integer_only) = if let Some(body) = lower.strip_suffix("ull") {
+        (body, true)
+    } else if let Some(body) = lower.strip_suffix("ll") {
+        (body, true)
+    } else if let Some(body) = lower.strip_suffix('i') {
+        (body, false)
+    } else {
+        (lower.as_str(), false)
+    };
+    if let Some(hex) = body.strip_prefix("0x") {
+        let (mantissa, exponent) = match hex.split_once('p') {
+            Some((m, e)) if !integer_only => (m, Some(e)),
+            Some(_) => return false,
+            None => (hex, None),
+        };
+        let digits = mantissa.replacen('.', "", 1);
+        !digits.is_empty()
+            && digits.chars().all(|c| c.is_ascii_hexdigit())
+            && !(integer_only && mantissa.contains('.'))
+            && exponent.is_none_or(|e| {
+                let e = e.strip_prefix(['+', '-']).unwrap_or(e);
+                !e.is_empty() && e.chars().all(|c| c.is_ascii_digit())
+            })
+    } else if integer_only {
+        !body.is_empty() && body.chars().all(|c| c.is_ascii_digit())
+    } else {
+        body.chars()
+            .all(|c| c.is_ascii_digit() || matches!(c, '.' | 'e' | '+' | '-'))
+            && body.parse::<f64>().is_ok()
+    }
+}
+
+/// state of the function being parsed.
+struct Function {
+    vararg: bool,
+    loops: usize,
+}
+
+struct Parser<'a> {
+    tokens: Vec<Token<'a>>,
+    pos: usize,
+    functions: Vec<Function>,
+}
+
+impl<'a> Parser<'a> {
+    fn current(&self) -> Token<'a> {
+        self.tokens[self.pos]
+    }
+
+    fn lookahead(&self) -> Token<'a> {
+        self.tokens[(self.pos + 1).min(self.tokens.len() - 1)]
+    }
+
+    fn next(&mut self) -> Token<'a> {
+        let token = self.current();
+        if token.kind != Kind::Eof {
+            self.pos += 1;
+        }
+        token
+    }
+
+    fn accept(&mut self, s: &str) -> bool {
+        if self.current().is(s) {
+            self.next();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn error(&self, message: String) -> SyntaxError {
+        let token = self.current();
+        SyntaxError {
+            line: token.line,
+            column: token.column,
+            message: format!("{} near {}", message, token.near()),
+        }
+    }
+
+    fn check(&mut self, s: &str) -> Result<(), SyntaxError> {
+        if self.accept(s) {
+            Ok(())
+        } else {
+            Err(self.error(format!("'{}' expected", s)))
+        }
+    }
+
+    /// `s` closing `who` opened at `line`.
+    fn check_match(&mut self, s: &str, who: &str, line: usize) -> Result<(), SyntaxError> {
+        if self.accept(s) {
+            Ok(())
+        } else if self.current().line == line {
+            Err(self.error(format!("'{}' expected", s)))
+        } else {
+            Err(self.error(format!(
+                "'{}' expected (to close '{}' at line {})",
+                s, who, line
+            )))
+        }
+    }
+
+    fn name(&mut self) -> Result<(), SyntaxError> {
+        if self.current().kind == Kind::Name {
+            self.next();
+            Ok(())
+        } else {
+            Err(self.error(String::from("<name> expected")))
+        }
+    }
+
+    fn function(&mut self) -> &mut Function {
+        // the main chunk is always on the stack.
+        self.functions.last_mut().expect("no function")
+    }
+
+    fn chunk(&mut self) -> Result<(), SyntaxError> {
+        self.block()?;
+        if self.current().kind == Kind::Eof {
+            Ok(())
+        } else {
+            Err(self.error(String::from("'<eof>' expected")))
+        }
+    }
+
+    fn block_follow(&self) -> bool {
+        let token = self.current();
+        token.kind == Kind::Eof
+            || ["else", "elseif", "end", "until"]
+                .iter()
+                .any(|s| token.is(s))
+    }
+
+    fn block(&mut self) -> Result<(), SyntaxError> {
+        while !self.block_follow() {
+            if self.accept("return") {
+                if !self.block_follow() && !self.current().is(";") {
+                    self.exp_list()?;
+                }
+                self.accept(";");
+                break;
+            }
+            self.statement()?;
+        }
+        Ok(())
+    }
+
+    fn loop_block(&mut self) -> Result<(), SyntaxError> {
+        self.function().loops += 1;
+        self.block()?;
+        self.function().loops -= 1;
+        Ok(())
+    }
+
+    fn statement(&mut self) -> Result<(), SyntaxError> {
+        let line = self.current().line;
+        if self.accept(";") {
+            Ok(())
+        } else if self.accept("if") {
+            self.exp()?;
+            self.check("then")?;
+            self.block()?;
+            while self.accept("elseif") {
+                self.exp()?;
+                self.check("then")?;
+                self.block()?;
+            }
+            if self.accept("else") {
+                self.block()?;
+            }
+            self.check_match("end", "if", line)
+        } else if self.accept("while") {
+            self.exp()?;
+            self.check("do")?;
+            self.loop_block()?;
+            self.check_match("end", "while", line)
+        } else if self.accept("do") {
+            self.block()?;
+            self.check_match("end", "do", line)
+        } else if self.accept("for") {
+            self.name()?;
+            if self.accept("=") {
+                self.exp()?;
+                self.check(",")?;
+                self.exp()?;
+                if self.accept(",") {
+                    self.exp()?;
+                }
+            } else {
+                while self.accept(",") {
+                    self.name()?;
+                }
+                self.check("in")?;
+                self.exp_list()?;
+            }
+            self.check("do")?;
+            self.loop_block()?;
+            self.check_match("end", "for", line)
+        } else if self.accept("repeat") {
+            self.loop_block()?;
+            self.check_match("until", "repeat", line)?;
+            self.exp()
+        } else if self.accept("function") {
+            self.name()?;
+            while self.accept(".") {
+                self.name()?;
+            }
+            if self.accept(":") {
+                self.name()?;
+            }
+            self.body(line)
+        } else if self.accept("local") {
+            if self.accept("function") {
+                self.name()?;
+                self.body(line)
+            } else {
+                self.name()?;
+                while self.accept(",") {
+                    self.name()?;
+                }
+                if self.accept("=") {
+                    self.exp_list()?;
+                }
+                Ok(())
+            }
+        } else if self.current().is("break") {
+            if self.function().loops == 0 {
+                return Err(self.error(String::from("no loop to break")));
+            }
+            self.next();
+            Ok(())
+        } else if self.accept("goto") {
+            self.name()
+        } else if self.accept("::") {
+            self.name()?;
+            self.check("::")
+        } else {
+            self.expression_statement()
+        }
+    }
+
+    /// function call or assignment.
+    fn expression_statement(&mut self) -> Result<(), SyntaxError> {
+        let (mut assignable, call) = self.suffixed_exp()?;
+        if !self.current().is("=") && !self.current().is(",") {
+            if call {
+                return Ok(());
+            }
+            return Err(self.error(String::from("'=' expected")));
+        }
+        loop {
+            if !assignable {
+                return Err(self.error(String::from("syntax error")));
+            }
+            if !self.accept(",") {
+                break;
+            }
+            assignable = self.suffixed_exp()?.0;
+        }
+        self.check("=")?;
+        self.exp_list()
+    }
+
+    /// parse `Name` or `(exp)`, returns whether it is assignable.
+    fn primary_exp(&mut self) -> Result<bool, SyntaxError> {
+        let token = self.current();
+        if token.kind == Kind::Name {
+            self.next();
+            Ok(true)
+        } else if self.accept("(") {
+            self.exp()?;
+            self.check_match(")", "(", token.line)?;
+            Ok(false)
+        } else {
+            Err(self.error(String::from("unexpected symbol")))
+        }
+    }
+
+    /// parse suffixed expression, returns whether it is (assignable, function call).
+    fn suffixed_exp(&mut self) -> Result<(bool, bool), SyntaxError> {
+        let mut state = (self.primary_exp()?, false);
+        loop {
+            let token = self.current();
+            if self.accept(".") {
+                self.name()?;
+                state = (true, false);
+            } else if self.accept("[") {
+                self.exp()?;
+                self.check("]")?;
+                state = (true, false);
+            } else if self.accept(":") {
+                self.name()?;
+                self.args()?;
+                state = (false, true);
+            } else if token.is("(") || token.is("{") || token.kind == Kind::Str {
+                self.args()?;
+                state = (false, true);
+            } else {
+                return Ok(state);
+            }
+        }
+    }
+
+    fn args(&mut self) -> Result<(), SyntaxError> {
+        let token = self.current();
+        if token.kind == Kind::Str {
+            self.next();
+            Ok(())
+        } else if token.is("{") {
+            self.table()
+        } else if token.is("(") {
+            if token.line != self.tokens[self.pos - 1].line {
+                return Err(self.error(String::from(
+                    "ambiguous syntax (function call x new statement)",
+                )));
+            }
+            self.next();
+            if !self.current().is(")") {
+                self.exp_list()?;
+            }
+            self.check_match(")", "(", token.line)
+        } else {
+            Err(self.error(String::from("function arguments expected")))
+        }
+    }
+
+    fn table(&mut self) -> Result<(), SyntaxError> {
+        let line = self.current().line;
+        self.check("{")?;
+        while !self.current().is("}") {
+            if self.accept("[") {
+                self.exp()?;
+                self.check("]")?;
+                self.check("=")?;
+                self.exp()?;
+            } else if self.current().kind == Kind::Name && self.lookahead().is("=") {
+                self.next();
+                self.next();
+                self.exp()?;
+            } else {
+                self.exp()?;
+            }
+            if !self.accept(",") && !self.accept(";") {
+                break;
+            }
+        }
+        self.check_match("}", "{", line)
+    }
+
+    /// parameters and body of a function opened at `line`.
+    fn body(&mut self, line: usize) -> Result<(), SyntaxError> {
+        self.check("(")?;
+        let mut vararg = false;
+        if !self.current().is(")") {
+            loop {
+                if self.accept("...") {
+                    vararg = true;
+                    break;
+                }
+                self.name()?;
+                if !self.accept(",") {
+                    break;
+                }
+            }
+        }
+        self.check(")")?;
+        self.functions.push(Function { vararg, loops: 0 });
+        self.block()?;
+        self.functions.pop();
+        self.check_match("end", "function", line)
+    }
+
+    fn simple_exp(&mut self) -> Result<(), SyntaxError> {
+        let token = self.current();
+        match token.kind {
+            Kind::Number | Kind::Str => {
+                self.next();
+                Ok(())
+            }
+            _ if token.is("nil") || token.is("true") || token.is("false") => {
+                self.next();
+                Ok(())
+            }
+            _ if token.is("...") => {
+                if !self.function().vararg {
+                    return Err(
+                        self.error(String::from("cannot use '...' outside a vararg function"))
+                    );
+                }
+                self.next();
+                Ok(())
+            }
+            _ if token.is("{") => self.table(),
+            _ if token.is("function") => {
+                self.next();
+                self.body(token.line)
+            }
+            _ => self.suffixed_exp().map(|_| ()),
+        }
+    }
+
+    fn exp(&mut self) -> Result<(), SyntaxError> {
+        loop {
+            while self.accept("not") || self.accept("-") || self.accept("#") {}
+            self.simple_exp()?;
+            if !BINARY_OPS.iter().any(|op| self.current().is(op)) {
+                return Ok(());
+            }
+            self.next();
+        }
+    }
+
+    fn exp_list(&mut self) -> Result<(), SyntaxError> {
+        self.exp()?;
+        while self.accept(",") {
+            self.exp()?;
+        }
+        Ok(())
+    }
+}
+
+/// check syntax of lua (luajit) code.
+pub fn check(code: &str) -> Result<(), SyntaxError> {
+    let mut lexer = Lexer {
+        src: code,
+        pos: 0,
+        line: 1,
+        line_start: 0,
+    };
+    // skip shebang.
+    if code.starts_with('#') {
+        while lexer.peek(0).is_some_and(|c| c != '\n' && c != '\r') {
+            lexer.bump();
+        }
+    }
+    let mut tokens = Vec::new();
+    loop {
+        let token = lexer.next_token()?;
+        tokens.push(token);
+        if token.kind == Kind::Eof {
+            break;
+        }
+    }
+    Parser {
+        tokens,
+        pos: 0,
+        // the main chunk is a vararg function.
+        functions: vec![Function {
+            vararg: true,
+            loops: 0,
+        }],
+    }
+    .chunk()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use rstest::rstest;
+
+    #[rstest(
+        arg,
+        case(""),
+        case("-- comment\n--[==[ long\ncomment ]==]"),
+        case(
+            "require('foo').setup({ a = 1, ['b'] = { 2, 3; }, c = function(...) return ... end })"
+        ),
+        case("vim.cmd([[\n  let g:foo = 1\n]])"),
+        case("local a, b = 1, 0x1p4\nlocal c = 1e-3 + .5 + 2ULL + 3i"),
+        case("local x = 0xE+1 - 0Xe-1 + 0x1P-2"),
+        case("local s = 'a\\'b\\n\\x41\\z\n   \\u{3042}\\0651'"),
+        case("local t = {}\nt.a.b[1]:c 'x' { y = 1 }"),
+        case("for i = 1, 10, 2 do if i > 5 then break end end"),
+        case("for k, v in pairs({}) do goto continue ::continue:: end"),
+        case("while true do repeat local x = 1 until x == 1 break end"),
+        case("local function f(a, ...) local x = select('#', ...) end"),
+        case("function M.a.b:c() return end"),
+        case("local x = not - # 'a' .. 'b' == 'c' and 1 or 2 ^ -3"),
+        case("return"),
+        case("do return 1; end;;"),
+        case("#!/usr/bin/env lua\nprint(...)")
+    )]
+    fn valid_syntax(arg: &str) {
+        // act:
+        let act = check(arg);
+
+        // assert:
+        assert_eq!(act, Ok(()));
+    }
+
+    #[test]
+    fn valid_runtime_syntax() {
+        // act:
+        let act = check(include_str!("../../../lua/oboro/init.lua"));
+
+        // assert:
+        assert_eq!(act, Ok(()));
+    }
+
+    #[rstest(
+        arg,
+        exp_line,
+        exp_column,
+        exp_message,
+        case("if true then", 1, 13, "'end' expected near '<eof>'"),
+        case(
+            "local function f()\n  return 1\n",
+            3,
+            1,
+            "'end' expected (to close 'function' at line 1) near '<eof>'"
+        ),
+        case("print('a'", 1, 10, "')' expected near '<eof>'"),
+        case("x", 1, 2, "'=' expected near '<eof>'"),
+        case("f() = 1", 1, 5, "syntax error near '='"),
+        case("local = 1", 1, 7, "<name> expected near '='"),
+        case("x = = 1", 1, 5, "unexpected symbol near '='"),
+        case("return 1\nx = 1", 2, 1, "'<eof>' expected near 'x'"),
+        case("local s = 'abc", 1, 11, "unfinished string near ''abc'"),
+        case("local s = 'a\\qb'", 1, 11, "invalid escape sequence near ''a\\q'"),
+        case(
+            "local s = [[\nabc",
+            2,
+            4,
+            "unfinished long string (starting at line 1) near '<eof>'"
+        ),
+        case("local n = 3.4.5", 1, 11, "malformed number near '3.4.5'"),
+        case("local n = 0xg", 1, 11, "malformed number near '0xg'"),
+        case("local n = 0xE+", 1, 15, "unexpected symbol near '<eof>'"),
+        case("break", 1, 1, "no loop to break near 'break'"),
+        case(
+            "local function f() return ... end",
+            1,
+            27,
+            "cannot use '...' outside a vararg function near '...'"
+        ),
+        case(
+            "local a = b\n(f)()",
+            2,
+            1,
+            "ambiguous syntax (function call x new statement) near '('"
+        ),
+        case("local x = 1 // 2", 1, 14, "unexpected symbol near '/'"),
+        // `]]` in vim script closes the `vim.cmd` wrapper.
+        case("vim.cmd([[\n  echo ']]'\n]])", 2, 11, "unfinished string near '''")
+    )]
+    fn invalid_syntax(arg: &str, exp_line: usize, exp_column: usize, exp_message: &str) {
+        // act:
+        let act = check(arg);
+
+        // assert:
+        assert_eq!(
+            act,
+            Err(SyntaxError {
+                line: exp_line,
+                column: exp_column,
+                message: String::from(exp_message),
+            })
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn check_never_panics(s in any::<String>()) {
+            // act:
+            let _ = check(&s);
+        }
+
+        #[test]
+        fn check_never_panics_on_lua_like(s in r#"[a-z0-9 \n\r\-\[\]=\.'"\\(){}:;,#~<>+*/^%]{0,40}"#) {
+            // act:
+            let _ = check(&s);
+        }
+    }
+}
@@ -0,0 +1,311 @@
+use crate::config::output::OboroConfig;
+use crate::config::{load_edges, LoadEdge};
+use anyhow::{bail, Result};
+use std::fmt::Write;
+
+/// keys of `map` whose values contain `id`.
+fn keys_of<'a, K, I>(map: I, id: &str) -> Vec<K>
+where
+    I: IntoIterator<Item = (&'a K, &'a Vec<&'a str>)>,
+    K: Clone + 'a,
+{
+    map.into_iter()
+        .filter(|(_, ids)| ids.contains(&id))
+        .map(|(k, _)| k.clone())
+        .collect()
+}
+
+/// write `label: a, b, c` (skip if empty).
+fn write_list(out: &mut String, label: &str, xs: &[String]) -> std::fmt::Result {
+    if xs.is_empty() {
+        return Ok(());
+    }
+    writeln!(out, "  {}: {}", label, xs.join(", "))
+}
+
+/// ids loaded by loading `id` directly (its dependencies and, for a bundle, its plugins).
+fn loads<'a>(edges: &[LoadEdge<'a>], id: &str) -> Vec<&'a str> {
+    edges
+        .iter()
+        .filter(|x| x.from == id)
+        .map(|x| x.to)
+        .collect()
+}
+
+/// ids whose loading also loads `id` (bundles containing it if `contains`, dependents otherwise).
+fn loaded_by<'a>(edges: &[LoadEdge<'a>], id: &str, contains: bool) -> Vec<&'a str> {
+    edges
+        .iter()
+        .filter(|x| x.to == id && x.contains == contains)
+        .map(|x| x.from)
+        .collect()
+}
+
+/// ids reachable from `id` by `next` (breadth first, excluding `id`).
+fn reachable<'a>(id: &'a str, next: impl Fn(&str) -> Vec<&'a str>) -> Vec<&'a str> {
+    let mut seen = vec![id];
+    let mut i = 0;
+    while i < seen.len() {
+        for x in next(seen[i]) {
+            if !seen.contains(&x) {
+                seen.push(x);
+            }
+        }
+        i += 1;
+    }
+    seen.split_off(1)
+}
+
+/// triggers loading `id` (e.g. `module foo`, `event User VeryLazy`).
+fn triggers_of(config: &OboroConfig, id: &str) -> Vec<String> {
+    let mods = keys_of(&config.mod_map, id)
+        .into_iter()
+        .map(|x| format!("module {}", x));
+    let evs = keys_of(&config.ev_map, id)
+        .into_iter()
+        .map(|(ev, pattern)| format!("event {} {}", ev, pattern));
+    let fts = keys_of(&config.ft_map, id)
+        .into_iter()
+        .map(|x| format!("filetype {}", x));
+    let cmds = keys_of(&config.cmd_map, id)
+        .into_iter()
+        .map(|x| format!("command {}", x));
+    let keys = keys_of(&config.key_map, id)
+        .into_iter()
+        .map(|(mode, lhs)| format!("key {} {}", mode, lhs));
+    let lazy = config.lazys.contains(&id).then(|| String::from("lazy"));
+    mods.chain(evs)
+        .chain(fts)
+        .chain(cmds)
+        .chain(keys)
+        .chain(lazy)
+        .collect()
+}
+
+/// explain how the plugin (or bundle) is configured.
+pub fn explain(config: &OboroConfig, id: &str) -> Result<String> {
+    let mut out = String::new();
+
+    if let Some(plugin) = config.startup_plugins.iter().find(|x| x.id == id) {
+        writeln!(out, "`{}` (start plugin)", plugin.id)?;
+        writeln!(out, "  plugin: {}", plugin.plugin)?;
+        writeln!(out, "  loaded at startup.")?;
+        return Ok(out);
+    }
+
+    let (kind, deps, dep_bundles, plugins) =
+        if let Some(plugin) = config.lazy_plugins.iter().find(|x| x.id == id) {
+            ("opt plugin", &plugin.deps, &plugin.dep_bundles, None)
+        } else if let Some(bundle) = config.bundles.iter().find(|x| x.id == id) {
+            (
+                "bundle",
+                &bundle.deps,
+                &bundle.dep_bundles,
+                Some(&bundle.plugins),
+            )
+        } else {
+            bail!("unknown id `{}`.", id)
+        };
+    let to_strings = |xs: &[&str]| xs.iter().map(|x| x.to_string()).collect::<Vec<_>>();
+
+    writeln!(out, "`{}` ({})", id, kind)?;
+    if let Some(plugins) = plugins {
+        write_list(&mut out, "plugins", &to_strings(plugins))?;
+    }
+    write_list(&mut out, "depends on plugins", &to_strings(deps))?;
+    write_list(&mut out, "depends on bundles", &to_strings(dep_bundles))?;
+
+    // triggers
+    let mods = keys_of(&config.mod_map, id);
+    let evs = keys_of(&config.ev_map, id)
+        .into_iter()
+        .map(|(ev, pattern)| format!("{} {}", ev, pattern))
+        .collect::<Vec<_>>();
+    let fts = keys_of(&config.ft_map, id);
+    let cmds = keys_of(&config.cmd_map, id);
+    let keys = keys_of(&config.key_map, id)
+        .into_iter()
+        .map(|(mode, lhs)| format!("{} {}", mode, lhs))
+        .collect::<Vec<_>>();
+    write_list(&mut out, "modules", &to_strings(&mods))?;
+    write_list(&mut out, "events", &evs)?;
+    write_list(&mut out, "filetypes", &to_strings(&fts))?;
+    write_list(&mut out, "commands", &to_strings(&cmds))?;
+    write_list(&mut out, "keys", &keys)?;
+    if config.lazys.contains(&id) {
+        writeln!(out, "  loaded lazily using timer.")?;
+    }
+
+    // dependents
+    let edges = load_edges(config);
+    let bundles = loaded_by(&edges, id, true);
+    let required_by = loaded_by(&edges, id, false);
+    write_list(&mut out, "bundled in", &to_strings(&bundles))?;
+    write_list(&mut out, "required by", &to_strings(&required_by))?;
+
+    // transitive
+    let through = reachable(id, |x| {
+        let mut ids = loaded_by(&edges, x, false);
+        ids.extend(loaded_by(&edges, x, true));
+        ids
+    });
+    let indirect_triggers = through
+        .iter()
+        .flat_map(|via| {
+            triggers_of(config, via)
+                .into_iter()
+                .map(move |x| format!("{} (via {})", x, via))
+        })
+        .collect::<Vec<_>>();
+    // other plugins of its bundles are loaded along when it is loaded through them.
+    let siblings = bundles
+        .iter()
+        .flat_map(|x| loads(&edges, x))
+        .collect::<Vec<_>>();
+    let along = reachable(id, |x| {
+        let mut ids = loads(&edges, x);
+        if x == id {
+            ids.extend(siblings.iter().copied());
+        }
+        ids
+    });
+    write_list(&mut out, "loaded through", &to_strings(&through))?;
+    write_list(&mut out, "also triggered by", &indirect_triggers)?;
+    write_list(&mut out, "loads along", &to_strings(&along))?;
+
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::output::{Bundle, LazyPlugin, StartupPlugin};
+    use std::collections::BTreeMap;
+
+    fn config() -> OboroConfig<'static> {
+        OboroConfig {
+            startup_plugins: vec![StartupPlugin {
+                id: "foo",
+                plugin: "/foo",
+                ..Default::default()
+            }],
+            lazy_plugins: vec![
+                LazyPlugin {
+                    id: "bar",
+                    deps: vec!["baz"],
+                    ..Default::default()
+                },
+                LazyPlugin {
+                    id: "baz",
+                    ..Default::default()
+                },
+            ],
+            bundles: vec![Bundle {
+                id: "hoge",
+                plugins: vec!["baz"],
+                ..Default::default()
+            }],
+            mod_map: BTreeMap::from([("baz", vec!["baz"])]),
+            ev_map: BTreeMap::from([(("User", "VeryLazy"), vec!["baz", "bar"])]),
+            key_map: BTreeMap::from([(("n", "<leader>b"), vec!["baz"])]),
+            lazys: vec!["baz"],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn explain_start_plugin() {
+        // act:
+        let act = explain(&config(), "foo").unwrap();
+
+        // assert:
+        assert_eq!(
+            act,
+            "`foo` (start plugin)\n  plugin: /foo\n  loaded at startup.\n"
+        );
+    }
+
+    #[test]
+    fn explain_opt_plugin() {
+        // act:
+        let act = explain(&config(), "baz").unwrap();
+
+        // assert:
+        assert_eq!(
+            act,
+            "`baz` (opt plugin)\n  \
+             modules: baz\n  \
+             events: User VeryLazy\n  \
+             keys: n <leader>b\n  \
+             loaded lazily using timer.\n  \
+             bundled in: hoge\n  \
+             required by: bar\n  \
+             loaded through: bar, hoge\n  \
+             also triggered by: event User VeryLazy (via bar)\n"
+        );
+    }
+
+    #[test]
+    fn explain_transitive() {
+        // arrange:
+        let config = OboroConfig {
+            lazy_plugins: vec![
+                LazyPlugin {
+                    id: "a",
+                    deps: vec!["b"],
+                    ..Default::default()
+                },
+                LazyPlugin {
+                    id: "b",
+                    dep_bundles: vec!["lsp"],
+                    ..Default::default()
+                },
+                LazyPlugin {
+                    id: "c",
+                    deps: vec!["d"],
+                    ..Default::default()
+                },
+                LazyPlugin {
+                    id: "d",
+                    ..Default::default()
+                },
+                LazyPlugin {
+                    id: "e",
+                    ..Default::default()
+                },
+            ],
+            bundles: vec![Bundle {
+                id: "lsp",
+                plugins: vec!["c", "e"],
+                ..Default::default()
+            }],
+            ft_map: BTreeMap::from([("rust", vec!["a"])]),
+            cmd_map: BTreeMap::from([("LspStart", vec!["lsp"])]),
+            lazys: vec!["b"],
+            ..Default::default()
+        };
+
+        // act:
+        let act = explain(&config, "c").unwrap();
+
+        // assert:
+        assert_eq!(
+            act,
+            "`c` (opt plugin)\n  \
+             depends on plugins: d\n  \
+             bundled in: lsp\n  \
+             loaded through: lsp, b, a\n  \
+             also triggered by: command LspStart (via lsp), lazy (via b), filetype rust (via a)\n  \
+             loads along: d, e\n"
+        );
+    }
+
+    #[test]
+    fn explain_unknown() {
+        // act:
+        let act = explain(&config(), "unknown");
+
+        // assert:
+        assert!(act.is_err());
+    }
+}
@@ -1,204 +1,453 @@
+pub mod lua;
+pub mod manifest;
+pub mod output;
+
 use crate::config::output::OboroConfig;
-use anyhow::{anyhow, Result};
-use std::collections::HashMap;
-use std::fs::{create_dir, File};
-use std::io::Write;
+use crate::generator::lua::LuaValue;
+use crate::generator::output::Output;
+use anyhow::{Context, Result};
+use log::debug;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// file name of the single-file output.
+pub const SINGLE_FILE_NAME: &str = "compiled.lua";
+
+/// output layout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputMode {
+    /// one file per plugin per concern (`deps/<ID>`, `fts/<FILE_TYPE>`, ...).
+    #[default]
+    Tree,
+    /// a single lua module containing all tables and config functions (ftdetect scripts stay files).
+    SingleFile,
+}
 
 /// vector to table.
 fn to_lua_table(v: &[&str]) -> String {
-    v.iter()
-        .fold(String::from("{"), |acc, x| acc + "'" + x + "',")
-        + "}"
+    LuaValue::from(v).to_string()
 }
 
-/// generate startup config.
-fn gen_startup(config: &OboroConfig, root: &str) -> Result<()> {
-    let path = String::from(root) + "/startup";
-    let mut file = File::create(&path)?;
-
-    // startup
-    for plugin in config.startup_plugins.iter() {
-        if plugin.startup.is_empty() {
-            continue;
-        }
-        write!(file, "-- {}\n{}\n", plugin.id, plugin.startup)?;
-    }
+/// runs a startup snippet as its own chunk, reporting errors with the owning id.
+const STARTUP_RUNNER: &str = r#"local function run(id, code)
+	local fn, err_msg = loadstring(code, "=[" .. id .. "] startup")
+	local ok = fn ~= nil
+	if ok then
+		ok, err_msg = pcall(fn)
+	end
+	if not ok then
+		print("[" .. id .. "] startup error: " .. (err_msg or "-- no msg --"))
+	end
+end
+"#;
 
-    // lazy
-    for plugin in config.lazy_plugins.iter() {
-        if plugin.startup.is_empty() {
-            continue;
-        }
-        write!(file, "-- {}\n{}\n", plugin.id, plugin.startup)?;
+/// startup code of all plugins (in execution order).
+fn startup_code(config: &OboroConfig) -> String {
+    if config.startups.is_empty() {
+        return String::new();
     }
+    config
+        .startups
+        .iter()
+        .fold(String::from(STARTUP_RUNNER), |acc, (id, startup)| {
+            acc + &format!("run({}, {})\n", lua::quote(id), lua::quote(startup))
+        })
+}
 
-    // bundle
-    for bundle in config.bundles.iter() {
-        if bundle.startup.is_empty() {
-            continue;
-        }
-        write!(file, "-- {}\n{}\n", bundle.id, bundle.startup)?;
-    }
+/// write `contents` to `path`.
+fn write(out: &mut dyn Output, path: &str, contents: &str) -> Result<()> {
+    out.write(path, contents.as_bytes())?;
+    debug!(path = path; "write");
+    Ok(())
+}
 
-    println!("write: {}", &path);
-    file.flush().map_err(|err| anyhow!(err))
+/// generate startup config.
+fn gen_startup(config: &OboroConfig, out: &mut dyn Output) -> Result<()> {
+    write(out, "startup", &startup_code(config))
 }
 
 /// generate configs.
-fn gen_config(config: &OboroConfig, root: &str) -> Result<()> {
+fn gen_config(config: &OboroConfig, out: &mut dyn Output) -> Result<()> {
     // setup
-    create_dir(String::from(root) + "/pre_cfgs")?;
-    create_dir(String::from(root) + "/cfgs")?;
-    create_dir(String::from(root) + "/deps")?;
-    create_dir(String::from(root) + "/plugin")?;
-    create_dir(String::from(root) + "/plugins")?;
+    out.create_dir("pre_cfgs")?;
+    out.create_dir("cfgs")?;
+    out.create_dir("deps")?;
+    out.create_dir("plugin")?;
+    out.create_dir("plugins")?;
 
     // lazy
     for plugin in config.lazy_plugins.iter() {
-        let pre_cfg_path = String::from(root) + "/pre_cfgs/" + plugin.id;
-        let cfg_path = String::from(root) + "/cfgs/" + plugin.id;
-        let deps_path = String::from(root) + "/deps/" + plugin.id;
-        let plugin_path = String::from(root) + "/plugin/" + plugin.id;
-        let plugins_path = String::from(root) + "/plugins/" + plugin.id;
-        let mut pre_cfg_file = File::create(&pre_cfg_path)?;
-        let mut cfg_file = File::create(&cfg_path)?;
-        let mut deps_file = File::create(&deps_path)?;
-        let mut plugin_file = File::create(&plugin_path)?;
-        let mut plugins_file = File::create(&plugins_path)?;
-        write!(pre_cfg_file, "{}", plugin.pre_config)?;
-        write!(cfg_file, "{}", plugin.config)?;
-        write!(
-            deps_file,
-            "return {}",
-            to_lua_table(&([&plugin.deps[..], &plugin.dep_bundles[..]]).concat())
+        let deps = [&plugin.deps[..], &plugin.dep_bundles[..]].concat();
+        write(out, &format!("pre_cfgs/{}", plugin.id), plugin.pre_config)?;
+        write(out, &format!("cfgs/{}", plugin.id), plugin.config)?;
+        write(
+            out,
+            &format!("deps/{}", plugin.id),
+            &format!("return {}", to_lua_table(&deps)),
+        )?;
+        write(
+            out,
+            &format!("plugin/{}", plugin.id),
+            &format!("return {}", LuaValue::from(plugin.id)),
         )?;
-        write!(plugin_file, "return '{}'", plugin.id)?;
-        write!(plugins_file, "return {{}}")?;
-        println!("write: {}", &pre_cfg_path);
-        println!("write: {}", &cfg_path);
-        println!("write: {}", &deps_path);
-        println!("write: {}", &plugin_path);
-        println!("write: {}", &plugins_path);
-        pre_cfg_file.flush().map_err(|err| anyhow!(err))?;
-        cfg_file.flush().map_err(|err| anyhow!(err))?;
-        deps_file.flush().map_err(|err| anyhow!(err))?;
-        plugin_file.flush().map_err(|err| anyhow!(err))?;
-        plugins_file.flush().map_err(|err| anyhow!(err))?;
+        write(out, &format!("plugins/{}", plugin.id), "return {}")?;
     }
 
     // bundle
     for bundle in config.bundles.iter() {
-        let pre_cfg_path = String::from(root) + "/pre_cfgs/" + bundle.id;
-        let cfg_path = String::from(root) + "/cfgs/" + bundle.id;
-        let deps_path = String::from(root) + "/deps/" + bundle.id;
-        let plugin_path = String::from(root) + "/plugin/" + bundle.id;
-        let plugins_path = String::from(root) + "/plugins/" + bundle.id;
-        let mut pre_cfg_file = File::create(&pre_cfg_path)?;
-        let mut cfg_file = File::create(&cfg_path)?;
-        let mut deps_file = File::create(&deps_path)?;
-        let mut plugin_file = File::create(&plugin_path)?;
-        let mut plugins_file = File::create(&plugins_path)?;
-        write!(pre_cfg_file, "{}", bundle.pre_config)?;
-        write!(cfg_file, "{}", bundle.config)?;
-        // write!(deps_file, "return {}", to_lua_table(bundle.deps))?;
-        write!(
-            deps_file,
-            "return {}",
-            to_lua_table(&([&bundle.deps[..], &bundle.dep_bundles[..]]).concat())
+        let deps = [&bundle.deps[..], &bundle.dep_bundles[..]].concat();
+        write(out, &format!("pre_cfgs/{}", bundle.id), bundle.pre_config)?;
+        write(out, &format!("cfgs/{}", bundle.id), bundle.config)?;
+        write(
+            out,
+            &format!("deps/{}", bundle.id),
+            &format!("return {}", to_lua_table(&deps)),
+        )?;
+        write(
+            out,
+            &format!("plugin/{}", bundle.id),
+            &format!("return {}", LuaValue::Nil),
+        )?;
+        write(
+            out,
+            &format!("plugins/{}", bundle.id),
+            &format!("return {}", to_lua_table(&bundle.plugins)),
         )?;
-        write!(plugin_file, "return nil")?;
-        write!(plugins_file, "return {}", to_lua_table(&bundle.plugins))?;
-        println!("write: {}", &pre_cfg_path);
-        println!("write: {}", &cfg_path);
-        println!("write: {}", &deps_path);
-        println!("write: {}", &plugin_path);
-        println!("write: {}", &plugins_path);
-        pre_cfg_file.flush().map_err(|err| anyhow!(err))?;
-        cfg_file.flush().map_err(|err| anyhow!(err))?;
-        deps_file.flush().map_err(|err| anyhow!(err))?;
-        plugin_file.flush().map_err(|err| anyhow!(err))?;
-        plugins_file.flush().map_err(|err| anyhow!(err))?;
     }
     Ok(())
 }
 
+/// escape string to be used as a file name.
+fn to_file_name(s: &str) -> String {
+    s.bytes().fold(String::new(), |mut acc, b| {
+        if b.is_ascii_alphanumeric() || b == b'_' || b == b'-' || b == b'.' {
+            acc.push(b as char);
+        } else {
+            acc.push_str(&format!("%{:02X}", b));
+        }
+        acc
+    })
+}
+
+/// file name of event trigger (`<EVENT>` or `<EVENT>@<PATTERN>`).
+fn to_event_key(ev: &str, pattern: &str) -> String {
+    if pattern == "*" {
+        to_file_name(ev)
+    } else {
+        to_file_name(ev) + "@" + &to_file_name(pattern)
+    }
+}
+
+/// configured events (`{ event, pattern, key }`).
+fn to_ev_tbl(config: &OboroConfig) -> LuaValue {
+    let ev_tbl = config
+        .evs
+        .iter()
+        .map(|&(ev, pattern)| {
+            LuaValue::Table(vec![
+                (String::from("event"), LuaValue::from(ev)),
+                (String::from("pattern"), LuaValue::from(pattern)),
+                (
+                    String::from("key"),
+                    LuaValue::Str(to_event_key(ev, pattern)),
+                ),
+            ])
+        })
+        .collect();
+    LuaValue::Array(ev_tbl)
+}
+
+/// event key -> ids.
+fn to_ev_files<'a>(config: &OboroConfig<'a>) -> BTreeMap<String, Vec<&'a str>> {
+    config
+        .ev_map
+        .iter()
+        .map(|(&(ev, pattern), ids)| (to_event_key(ev, pattern), ids.clone()))
+        .collect()
+}
+
+/// trigger map with keys escaped as file names (looked up by the escaped name at runtime).
+fn to_file_keys<'a>(kvp: &BTreeMap<&str, Vec<&'a str>>) -> BTreeMap<String, Vec<&'a str>> {
+    kvp.iter()
+        .map(|(k, ids)| (to_file_name(k), ids.clone()))
+        .collect()
+}
+
+/// configured key mappings (`{ mode, lhs, desc, ids }`).
+fn to_key_tbl(config: &OboroConfig) -> LuaValue {
+    let key_tbl = config
+        .keys
+        .iter()
+        .map(|key| {
+            let ids = config
+                .key_map
+                .get(&(key.mode, key.lhs))
+                .map(|ids| LuaValue::from(&ids[..]))
+                .unwrap_or(LuaValue::Array(vec![]));
+            LuaValue::Table(vec![
+                (String::from("mode"), LuaValue::from(key.mode)),
+                (String::from("lhs"), LuaValue::from(key.lhs)),
+                (String::from("desc"), LuaValue::from(key.desc)),
+                (String::from("ids"), ids),
+            ])
+        })
+        .collect();
+    LuaValue::Array(key_tbl)
+}
+
+/// key value (vector) pair to table.
+fn to_lua_map<K: AsRef<str>>(kvp: &BTreeMap<K, Vec<&str>>) -> LuaValue {
+    LuaValue::Table(
+        kvp.iter()
+            .map(|(k, v)| (String::from(k.as_ref()), LuaValue::from(&v[..])))
+            .collect(),
+    )
+}
+
 /// generate key value (vector) pair.
-fn gen_kvp(kvp: &HashMap<&str, Vec<&str>>, path_prefix: &str) -> Result<()> {
+fn gen_kvp<K: AsRef<str>>(
+    kvp: &BTreeMap<K, Vec<&str>>,
+    dir: &str,
+    out: &mut dyn Output,
+) -> Result<()> {
+    out.create_dir(dir)?;
     for (k, v) in kvp {
-        let path = String::from(path_prefix) + k;
-        let mut file = File::create(&path)?;
-        write!(file, "return {}", to_lua_table(v))?;
-        println!("write: {}", &path);
-        file.flush().map_err(|err| anyhow!(err))?;
+        write(
+            out,
+            &format!("{}/{}", dir, k.as_ref()),
+            &format!("return {}", to_lua_table(v)),
+        )?;
     }
     Ok(())
 }
 
-/// generate configs.
-pub fn generate(config: &OboroConfig, root: &str) -> Result<()> {
+/// `ftdetect/*.{vim,lua}` of opt plugins as (plugin id, path in `ftdetect/`, contents), sorted by path.
+///
+/// each plugin gets its own directory, so that file names can not collide between plugins.
+fn collect_ftdetects<'a>(config: &OboroConfig<'a>) -> Result<Vec<(&'a str, String, Vec<u8>)>> {
+    let mut files = Vec::new();
+    for plugin in config.lazy_plugins.iter() {
+        let dir = Path::new(plugin.plugin).join("ftdetect");
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => continue,
+            Err(err) => {
+                return Err(err).with_context(|| format!("failed to read `{}`", dir.display()))
+            }
+        };
+        for entry in entries {
+            let path = entry?.path();
+            let (Some(name), true) = (
+                path.file_name().and_then(|x| x.to_str()),
+                path.extension().is_some_and(|x| x == "vim" || x == "lua"),
+            ) else {
+                continue;
+            };
+            let contents =
+                fs::read(&path).with_context(|| format!("failed to read `{}`", path.display()))?;
+            files.push((
+                plugin.id,
+                to_file_name(plugin.id) + "/" + &to_file_name(name),
+                contents,
+            ));
+        }
+    }
+    files.sort_by(|a, b| a.1.cmp(&b.1));
+    Ok(files)
+}
+
+/// copy ftdetect scripts of opt plugins (sourced at startup as they are not in runtimepath yet)
+/// and return their table (`{ id, group, file }`, sourced in the augroup `group`).
+fn gen_ftdetect(config: &OboroConfig, out: &mut dyn Output) -> Result<LuaValue> {
+    let files = collect_ftdetects(config)?;
+    out.create_dir("ftdetect")?;
+    let mut ftdetect_tbl = Vec::new();
+    for (id, file, contents) in files {
+        if let Some((dir, _)) = file.split_once('/') {
+            out.create_dir(&format!("ftdetect/{}", dir))?;
+        }
+        let path = format!("ftdetect/{}", file);
+        out.write(&path, &contents)?;
+        debug!(path = path; "write");
+        ftdetect_tbl.push(LuaValue::Table(vec![
+            (String::from("id"), LuaValue::from(id)),
+            (
+                String::from("group"),
+                LuaValue::Str(format!("oboro_ftdetect_{}", to_file_name(id))),
+            ),
+            (String::from("file"), LuaValue::Str(file)),
+        ]));
+    }
+    Ok(LuaValue::Array(ftdetect_tbl))
+}
+
+/// generate configs as a tree of small files.
+fn gen_tree(config: &OboroConfig, out: &mut dyn Output) -> Result<()> {
     // start
-    gen_startup(config, root)?;
+    gen_startup(config, out)?;
 
     // opt
-    gen_config(config, root)?;
+    gen_config(config, out)?;
 
     // modules
-    let mod_tbl_path = String::from(root) + "/mod_tbl";
-    let mut mod_tbl_file = File::create(&mod_tbl_path)?;
-    write!(mod_tbl_file, "return {}", to_lua_table(&config.mods))?;
-    println!("write: {}", &mod_tbl_path);
-    mod_tbl_file.flush().map_err(|err| anyhow!(err))?;
-    create_dir(String::from(root) + "/mods")?;
-    let mods_path = String::from(root) + "/mods/";
-    gen_kvp(&config.mod_map, &mods_path)?;
+    write(
+        out,
+        "mod_idx",
+        &format!("return {}", to_lua_map(&config.mod_map)),
+    )?;
 
     // events
-    let ev_tbl_path = String::from(root) + "/ev_tbl";
-    let mut evs_file = File::create(&ev_tbl_path)?;
-    write!(evs_file, "return {}", to_lua_table(&config.evs))?;
-    println!("write: {}", &ev_tbl_path);
-    evs_file.flush().map_err(|err| anyhow!(err))?;
-    create_dir(String::from(root) + "/evs")?;
-    let evs_path = String::from(root) + "/evs/";
-    gen_kvp(&config.ev_map, &evs_path)?;
+    write(out, "ev_tbl", &format!("return {}", to_ev_tbl(config)))?;
+    gen_kvp(&to_ev_files(config), "evs", out)?;
 
     // filetypes
-    let ft_tbl_path = String::from(root) + "/ft_tbl";
-    let mut fts_file = File::create(&ft_tbl_path)?;
-    write!(fts_file, "return {}", to_lua_table(&config.fts))?;
-    println!("write: {}", &ft_tbl_path);
-    fts_file.flush().map_err(|err| anyhow!(err))?;
-    create_dir(String::from(root) + "/fts")?;
-    let fts_path = String::from(root) + "/fts/";
-    gen_kvp(&config.ft_map, &fts_path)?;
+    write(
+        out,
+        "ft_tbl",
+        &format!("return {}", to_lua_table(&config.fts)),
+    )?;
+    gen_kvp(&to_file_keys(&config.ft_map), "fts", out)?;
 
     // commands
-    let cmd_tbl_path = String::from(root) + "/cmd_tbl";
-    let mut cmds_file = File::create(&cmd_tbl_path)?;
-    write!(cmds_file, "return {}", to_lua_table(&config.cmds))?;
-    println!("write: {}", &cmd_tbl_path);
-    cmds_file.flush().map_err(|err| anyhow!(err))?;
-    create_dir(String::from(root) + "/cmds")?;
-    let cmds_path = String::from(root) + "/cmds/";
-    gen_kvp(&config.cmd_map, &cmds_path)?;
+    write(
+        out,
+        "cmd_tbl",
+        &format!("return {}", to_lua_table(&config.cmds)),
+    )?;
+    gen_kvp(&to_file_keys(&config.cmd_map), "cmds", out)?;
+
+    // keys
+    write(out, "key_tbl", &format!("return {}", to_key_tbl(config)))?;
+
+    // ftdetect
+    let ftdetect_tbl = gen_ftdetect(config, out)?;
+    write(out, "ftdetect_tbl", &format!("return {}", ftdetect_tbl))?;
 
     // lazy
-    let lazy_path = String::from(root) + "/lazy";
-    let mut lazy_file = File::create(&lazy_path)?;
-    write!(lazy_file, "return {}", to_lua_table(&config.lazys))?;
-    println!("write: {}", lazy_path);
-    lazy_file.flush().map_err(|err| anyhow!(err))
+    write(
+        out,
+        "lazy",
+        &format!("return {}", to_lua_table(&config.lazys)),
+    )
+}
+
+/// vararg lua function wrapping `code` (configs are chunks, so they may use `...`).
+fn to_lua_function(code: &str) -> String {
+    format!("function(...)\n{}\nend", code)
+}
+
+/// generate configs as a single lua module.
+fn gen_single(config: &OboroConfig, out: &mut dyn Output) -> Result<()> {
+    let mut code = String::from("local M = {}\n");
+
+    // start
+    writeln!(code, "M.startup = function()\n{}end", startup_code(config))?;
+
+    // opt
+    let configs = config
+        .lazy_plugins
+        .iter()
+        .map(|x| (x.id, x.pre_config, x.config))
+        .chain(
+            config
+                .bundles
+                .iter()
+                .map(|x| (x.id, x.pre_config, x.config)),
+        )
+        .collect::<Vec<_>>();
+    writeln!(code, "M.pre_cfgs = {{}}")?;
+    writeln!(code, "M.cfgs = {{}}")?;
+    for (id, pre_config, config) in configs.iter() {
+        let key = lua::quote(id);
+        if !pre_config.is_empty() {
+            writeln!(
+                code,
+                "M.pre_cfgs[{}] = {}",
+                key,
+                to_lua_function(pre_config)
+            )?;
+        }
+        if !config.is_empty() {
+            writeln!(code, "M.cfgs[{}] = {}", key, to_lua_function(config))?;
+        }
+    }
+    let deps = config
+        .lazy_plugins
+        .iter()
+        .map(|x| (x.id, [&x.deps[..], &x.dep_bundles[..]].concat()))
+        .chain(
+            config
+                .bundles
+                .iter()
+                .map(|x| (x.id, [&x.deps[..], &x.dep_bundles[..]].concat())),
+        )
+        .collect::<BTreeMap<_, _>>();
+    let plugin = LuaValue::Table(
+        config
+            .lazy_plugins
+            .iter()
+            .map(|x| (String::from(x.id), LuaValue::from(x.id)))
+            .collect(),
+    );
+    let plugins = config
+        .lazy_plugins
+        .iter()
+        .map(|x| (x.id, vec![]))
+        .chain(config.bundles.iter().map(|x| (x.id, x.plugins.clone())))
+        .collect::<BTreeMap<_, _>>();
+    writeln!(code, "M.deps = {}", to_lua_map(&deps))?;
+    writeln!(code, "M.plugin = {}", plugin)?;
+    writeln!(code, "M.plugins = {}", to_lua_map(&plugins))?;
+
+    // triggers
+    writeln!(code, "M.mod_idx = {}", to_lua_map(&config.mod_map))?;
+    writeln!(code, "M.ev_tbl = {}", to_ev_tbl(config))?;
+    writeln!(code, "M.evs = {}", to_lua_map(&to_ev_files(config)))?;
+    writeln!(code, "M.ft_tbl = {}", to_lua_table(&config.fts))?;
+    writeln!(
+        code,
+        "M.fts = {}",
+        to_lua_map(&to_file_keys(&config.ft_map))
+    )?;
+    writeln!(code, "M.cmd_tbl = {}", to_lua_table(&config.cmds))?;
+    writeln!(
+        code,
+        "M.cmds = {}",
+        to_lua_map(&to_file_keys(&config.cmd_map))
+    )?;
+    writeln!(code, "M.key_tbl = {}", to_key_tbl(config))?;
+    writeln!(code, "M.ftdetect_tbl = {}", gen_ftdetect(config, out)?)?;
+    writeln!(code, "M.lazy = {}", to_lua_table(&config.lazys))?;
+
+    code += "return M";
+    write(out, SINGLE_FILE_NAME, &code)
+}
+
+/// generate configs into `out`.
+pub fn generate(config: &OboroConfig, out: &mut dyn Output, mode: OutputMode) -> Result<()> {
+    match mode {
+        OutputMode::Tree => gen_tree(config, out),
+        OutputMode::SingleFile => gen_single(config, out),
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::input::OboroPluginConfig;
+    use crate::config::output::LazyPlugin;
+    use crate::config::resolve;
+    use crate::generator::output::Memory;
     use rstest::rstest;
 
     #[rstest(arg, exp,
         case(vec!["foo"], "{'foo',}"),
         case(vec!["foo", "bar"], "{'foo','bar',}"),
         case(vec![], "{}"),
+        case(vec!["it's", "a\\b\n"], r"{'it\'s','a\\b\n',}"),
     )]
     fn vector_to_table(arg: Vec<&str>, exp: String) {
         // act:
@@ -207,4 +456,270 @@ mod tests {
         // assert:
         assert_eq!(act, exp);
     }
+
+    #[rstest(
+        arg_ev,
+        arg_pattern,
+        exp,
+        case("BufRead", "*", "BufRead"),
+        case("User", "VeryLazy", "User@VeryLazy"),
+        case("BufReadPre", "*.rs", "BufReadPre@%2A.rs"),
+        case("BufEnter", "/home/foo/*", "BufEnter@%2Fhome%2Ffoo%2F%2A")
+    )]
+    fn event_to_key(arg_ev: &str, arg_pattern: &str, exp: &str) {
+        // act:
+        let act = to_event_key(arg_ev, arg_pattern);
+
+        // assert:
+        assert_eq!(act, exp);
+    }
+
+    fn fixture() -> OboroPluginConfig {
+        serde_json::from_str::<OboroPluginConfig>(
+            r#"{
+                "startPlugins": [
+                    { "id": "s2", "plugin": "s2", "startup": "-- s2", "priority": 0, "before": [], "after": [] },
+                    { "id": "s1", "plugin": "s1", "startup": "-- s1", "priority": 10, "before": [], "after": [] }
+                ],
+                "optPlugins": [
+                    { "id": "o3", "plugin": "o3", "startup": "-- o3", "priority": 0, "before": [], "after": [], "preConfig": "", "config": "",
+                      "deps": ["o1", "o2"], "depBundles": [], "mods": ["m3", "m1"], "evs": [{ "event": "InsertEnter", "pattern": null }],
+                      "fts": ["rust", "nix"], "cmds": ["Cmd3"], "lazy": true,
+                      "keys": [{ "lhs": "<leader>o", "modes": ["x", "n"], "desc": "o3's key" }] },
+                    { "id": "o1", "plugin": "o1", "startup": "-- o1", "priority": 0, "before": [], "after": [], "preConfig": "", "config": "require('o1').setup()",
+                      "deps": [], "depBundles": [], "mods": ["m1", "m2"], "evs": [{ "event": "InsertEnter", "pattern": null }, { "event": "User", "pattern": "Very/Lazy" }],
+                      "fts": ["rust"], "cmds": ["Cmd1"], "lazy": true,
+                      "keys": [{ "lhs": "<leader>o", "modes": ["n"], "desc": null }] },
+                    { "id": "o2", "plugin": "o2", "startup": "", "priority": 0, "before": [], "after": [], "preConfig": "", "config": "",
+                      "deps": [], "depBundles": [], "mods": [], "evs": [],
+                      "fts": [], "cmds": [], "lazy": false, "keys": [] }
+                ],
+                "bundles": [
+                    { "id": "b1", "plugins": ["o2", "o1"], "startup": "-- b1", "priority": 0, "before": [], "after": [], "preConfig": "", "config": "",
+                      "deps": [], "depBundles": [], "mods": ["m2"], "evs": [{ "event": "User", "pattern": "Very/Lazy" }],
+                      "fts": ["nix"], "cmds": ["Cmd1"], "lazy": true, "keys": [] }
+                ]
+            }"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn isolate_startup() {
+        // arrange:
+        let config = OboroConfig {
+            startups: vec![("foo", "vim.g.foo = 'foo'\nerror('foo')")],
+            ..Default::default()
+        };
+
+        // act:
+        let act = startup_code(&config);
+
+        // assert:
+        assert_eq!(
+            act,
+            String::from(STARTUP_RUNNER)
+                + r"run('foo', 'vim.g.foo = \'foo\'\nerror(\'foo\')')"
+                + "\n"
+        );
+        // lua 5.1 `load` does not take a string.
+        assert!(act.contains(r#"loadstring(code, "=[" .. id .. "] startup")"#));
+        assert_eq!(startup_code(&OboroConfig::default()), "");
+    }
+
+    #[test]
+    fn generate_deterministic() {
+        // arrange:
+        // parse and resolve twice, so that nondeterminism of the resolver is also caught.
+        let src1 = fixture();
+        let src2 = fixture();
+        let mut out1 = Memory::default();
+        let mut out2 = Memory::default();
+
+        // act:
+        generate(&resolve(&src1).unwrap(), &mut out1, OutputMode::Tree).unwrap();
+        generate(&resolve(&src2).unwrap(), &mut out2, OutputMode::Tree).unwrap();
+
+        // assert:
+        assert_eq!(out1.files, out2.files);
+        assert_eq!(out1, out2);
+        assert_eq!(
+            out1.read("startup").unwrap(),
+            String::from(STARTUP_RUNNER)
+                + "run('s1', '-- s1')\nrun('s2', '-- s2')\nrun('o3', '-- o3')\n\
+                   run('o1', '-- o1')\nrun('b1', '-- b1')\n"
+        );
+        assert!(out1.read("mod_tbl").is_none() && !out1.dirs.contains("mods"));
+        assert_eq!(
+            out1.read("mod_idx").unwrap(),
+            "return {m1={'o3','o1',},m2={'o1','b1',},m3={'o3',},}"
+        );
+        assert_eq!(
+            out1.read("ev_tbl").unwrap(),
+            "return {{event='InsertEnter',pattern='*',key='InsertEnter',},\
+             {event='User',pattern='Very/Lazy',key='User@Very%2FLazy',},}"
+        );
+        assert_eq!(
+            out1.read("evs/User@Very%2FLazy").unwrap(),
+            "return {'o1','b1',}"
+        );
+        assert_eq!(
+            out1.read("key_tbl").unwrap(),
+            "return {{mode='n',lhs='<leader>o',desc='o3\\'s key',ids={'o3','o1',},},\
+             {mode='x',lhs='<leader>o',desc='o3\\'s key',ids={'o3',},},}"
+        );
+        assert_eq!(out1.read("lazy").unwrap(), "return {'o3','o1','b1',}");
+    }
+
+    #[test]
+    fn generate_single_file() {
+        // arrange:
+        let src = fixture();
+        let mut out1 = Memory::default();
+        let mut out2 = Memory::default();
+
+        // act:
+        let config = resolve(&src).unwrap();
+        generate(&config, &mut out1, OutputMode::SingleFile).unwrap();
+        generate(&config, &mut out2, OutputMode::SingleFile).unwrap();
+
+        // assert:
+        assert_eq!(out1, out2);
+        assert_eq!(out1.files.len(), 1);
+        let act = out1.read(SINGLE_FILE_NAME).unwrap();
+        assert!(act.starts_with(
+            &(String::from("local M = {}\nM.startup = function()\n") + STARTUP_RUNNER)
+        ));
+        assert!(act.contains("\nM.cfgs['o1'] = function(...)\nrequire('o1').setup()\nend\n"));
+        assert!(!act.contains("M.pre_cfgs['o1']"));
+        assert!(act.contains("\nM.deps = {b1={},o1={},o2={},o3={'o1','o2',},}\n"));
+        assert!(act.contains("\nM.plugin = {o3='o3',o1='o1',o2='o2',}\n"));
+        assert!(act
+            .contains("\nM.evs = {InsertEnter={'o3','o1',},['User@Very%2FLazy']={'o1','b1',},}\n"));
+        assert!(act.contains("\nM.lazy = {'o3','o1','b1',}\n"));
+        assert!(act.ends_with("return M"));
+    }
+
+    #[rstest(mode, case(OutputMode::Tree), case(OutputMode::SingleFile))]
+    fn generate_ftdetect(mode: OutputMode) {
+        // arrange:
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().to_str().unwrap();
+        fs::create_dir_all(dir.path().join("ftdetect")).unwrap();
+        fs::write(
+            dir.path().join("ftdetect/foo.vim"),
+            "au BufRead *.foo setf foo",
+        )
+        .unwrap();
+        fs::write(dir.path().join("ftdetect/bar.lua"), "vim.filetype.add({})").unwrap();
+        fs::write(dir.path().join("ftdetect/README.md"), "").unwrap();
+        let config = OboroConfig {
+            lazy_plugins: vec![
+                LazyPlugin {
+                    id: "foo",
+                    plugin: root,
+                    ..Default::default()
+                },
+                LazyPlugin {
+                    id: "none",
+                    plugin: "/nonexistent",
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+        let mut out = Memory::default();
+
+        // act:
+        generate(&config, &mut out, mode).unwrap();
+
+        // assert:
+        assert_eq!(
+            out.read("ftdetect/foo/foo.vim"),
+            Some("au BufRead *.foo setf foo")
+        );
+        assert_eq!(
+            out.read("ftdetect/foo/bar.lua"),
+            Some("vim.filetype.add({})")
+        );
+        assert_eq!(
+            out.files
+                .keys()
+                .filter(|x| x.starts_with("ftdetect/"))
+                .count(),
+            2
+        );
+        let tbl = "{{id='foo',group='oboro_ftdetect_foo',file='foo/bar.lua',},\
+                   {id='foo',group='oboro_ftdetect_foo',file='foo/foo.vim',},}";
+        match mode {
+            OutputMode::Tree => {
+                assert_eq!(out.read("ftdetect_tbl"), Some(&*format!("return {}", tbl)))
+            }
+            OutputMode::SingleFile => assert!(out
+                .read(SINGLE_FILE_NAME)
+                .unwrap()
+                .contains(&format!("\nM.ftdetect_tbl = {}\n", tbl))),
+        }
+    }
+
+    #[test]
+    fn ftdetect_no_collision() {
+        // arrange:
+        let dir1 = tempfile::tempdir().unwrap();
+        let dir2 = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir1.path().join("ftdetect")).unwrap();
+        fs::create_dir_all(dir2.path().join("ftdetect")).unwrap();
+        fs::write(dir1.path().join("ftdetect/c.vim"), "\" a.b").unwrap();
+        fs::write(dir2.path().join("ftdetect/b.c.vim"), "\" a").unwrap();
+        let config = OboroConfig {
+            lazy_plugins: vec![
+                LazyPlugin {
+                    id: "a.b",
+                    plugin: dir1.path().to_str().unwrap(),
+                    ..Default::default()
+                },
+                LazyPlugin {
+                    id: "a",
+                    plugin: dir2.path().to_str().unwrap(),
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+        let mut out = Memory::default();
+
+        // act:
+        generate(&config, &mut out, OutputMode::Tree).unwrap();
+
+        // assert:
+        assert_eq!(out.read("ftdetect/a.b/c.vim"), Some("\" a.b"));
+        assert_eq!(out.read("ftdetect/a/b.c.vim"), Some("\" a"));
+    }
+
+    #[rstest(mode, case(OutputMode::Tree), case(OutputMode::SingleFile))]
+    fn escape_trigger_keys(mode: OutputMode) {
+        // arrange:
+        let config = OboroConfig {
+            ft_map: BTreeMap::from([("a/b", vec!["foo"])]),
+            cmd_map: BTreeMap::from([("Foo", vec!["foo"])]),
+            ..Default::default()
+        };
+        let mut out = Memory::default();
+
+        // act:
+        generate(&config, &mut out, mode).unwrap();
+
+        // assert:
+        match mode {
+            OutputMode::Tree => {
+                assert_eq!(out.read("fts/a%2Fb"), Some("return {'foo',}"));
+                assert_eq!(out.read("cmds/Foo"), Some("return {'foo',}"));
+            }
+            OutputMode::SingleFile => {
+                let act = out.read(SINGLE_FILE_NAME).unwrap();
+                assert!(act.contains("\nM.fts = {['a%2Fb']={'foo',},}\n"));
+                assert!(act.contains("\nM.cmds = {Foo={'foo',},}\n"));
+            }
+        }
+    }
 }
@@ -0,0 +1,385 @@
+use std::fmt;
+
+/// lua reserved words (can not be used as a bare table key).
+pub const KEYWORDS: [&str; 22] = [
+    "and", "break", "do", "else", "elseif", "end", "false", "for", "function", "goto", "if", "in",
+    "local", "nil", "not", "or", "repeat", "return", "then", "true", "until", "while",
+];
+
+/// lua literal.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LuaValue {
+    Nil,
+    Bool(bool),
+    Integer(i64),
+    Number(f64),
+    Str(String),
+    /// `{ v1, v2, ... }`
+    Array(Vec<LuaValue>),
+    /// `{ k1 = v1, k2 = v2, ... }`
+    Table(Vec<(String, LuaValue)>),
+}
+
+impl From<bool> for LuaValue {
+    fn from(v: bool) -> Self {
+        LuaValue::Bool(v)
+    }
+}
+
+impl From<i64> for LuaValue {
+    fn from(v: i64) -> Self {
+        LuaValue::Integer(v)
+    }
+}
+
+impl From<f64> for LuaValue {
+    fn from(v: f64) -> Self {
+        LuaValue::Number(v)
+    }
+}
+
+impl From<&str> for LuaValue {
+    fn from(v: &str) -> Self {
+        LuaValue::Str(String::from(v))
+    }
+}
+
+impl<T: Into<LuaValue>> From<Option<T>> for LuaValue {
+    fn from(v: Option<T>) -> Self {
+        v.map_or(LuaValue::Nil, Into::into)
+    }
+}
+
+impl<T: Into<LuaValue> + Clone> From<&[T]> for LuaValue {
+    fn from(v: &[T]) -> Self {
+        LuaValue::Array(v.iter().cloned().map(Into::into).collect())
+    }
+}
+
+/// quote string as lua string literal.
+pub fn quote(s: &str) -> String {
+    let mut quoted = String::with_capacity(s.len() + 2);
+    quoted.push('\'');
+    for c in s.chars() {
+        match c {
+            '\\' => quoted.push_str("\\\\"),
+            '\'' => quoted.push_str("\\'"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            // always 3 digits, so that a following digit is not taken as part of the escape.
+            c if c.is_ascii_control() => quoted.push_str(&format!("\\{:03}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('\'');
+    quoted
+}
+
+/// whether `s` can be used as a bare table key.
+fn is_identifier(s: &str) -> bool {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' => {}
+        _ => return false,
+    }
+    chars.all(|c| c.is_ascii_alphanumeric() || c == '_') && !KEYWORDS.contains(&s)
+}
+
+impl fmt::Display for LuaValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LuaValue::Nil => write!(f, "nil"),
+            LuaValue::Bool(v) => write!(f, "{}", v),
+            LuaValue::Integer(v) => write!(f, "{}", v),
+            LuaValue::Number(v) if v.is_nan() => write!(f, "0/0"),
+            LuaValue::Number(v) if v.is_infinite() => {
+                write!(f, "{}", if *v > 0.0 { "1/0" } else { "-1/0" })
+            }
+            LuaValue::Number(v) => write!(f, "{:?}", v),
+            LuaValue::Str(v) => write!(f, "{}", quote(v)),
+            LuaValue::Array(vs) => {
+                write!(f, "{{")?;
+                for v in vs {
+                    write!(f, "{},", v)?;
+                }
+                write!(f, "}}")
+            }
+            LuaValue::Table(kvs) => {
+                write!(f, "{{")?;
+                for (k, v) in kvs {
+                    if is_identifier(k) {
+                        write!(f, "{}={},", k, v)?;
+                    } else {
+                        write!(f, "[{}]={},", quote(k), v)?;
+                    }
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::syntax;
+    use proptest::prelude::*;
+    use rstest::rstest;
+
+    /// minimal parser for the lua literals emitted by `LuaValue` (test only).
+    struct Parser<'a> {
+        src: &'a [u8],
+        pos: usize,
+    }
+
+    impl<'a> Parser<'a> {
+        fn parse(src: &'a str) -> Result<LuaValue, String> {
+            let mut p = Parser {
+                src: src.as_bytes(),
+                pos: 0,
+            };
+            let v = p.value()?;
+            if p.pos != p.src.len() {
+                return Err(format!("trailing input at {}", p.pos));
+            }
+            Ok(v)
+        }
+
+        fn peek(&self) -> Option<u8> {
+            self.src.get(self.pos).copied()
+        }
+
+        fn eat(&mut self, s: &str) -> bool {
+            if self.src[self.pos..].starts_with(s.as_bytes()) {
+                self.pos += s.len();
+                true
+            } else {
+                false
+            }
+        }
+
+        fn value(&mut self) -> Result<LuaValue, String> {
+            match self.peek() {
+                Some(b'\'') | Some(b'"') => self.string().map(LuaValue::Str),
+                Some(b'{') => self.table(),
+                _ if self.eat("nil") => Ok(LuaValue::Nil),
+                _ if self.eat("true") => Ok(LuaValue::Bool(true)),
+                _ if self.eat("false") => Ok(LuaValue::Bool(false)),
+                _ if self.eat("0/0") => Ok(LuaValue::Number(f64::NAN)),
+                _ if self.eat("1/0") => Ok(LuaValue::Number(f64::INFINITY)),
+                _ if self.eat("-1/0") => Ok(LuaValue::Number(f64::NEG_INFINITY)),
+                _ => self.number(),
+            }
+        }
+
+        fn number(&mut self) -> Result<LuaValue, String> {
+            let start = self.pos;
+            while let Some(c) = self.peek() {
+                if c.is_ascii_alphanumeric() || c == b'.' || c == b'-' || c == b'+' {
+                    self.pos += 1;
+                } else {
+                    break;
+                }
+            }
+            let s = std::str::from_utf8(&self.src[start..self.pos]).unwrap();
+            if let Ok(v) = s.parse::<i64>() {
+                Ok(LuaValue::Integer(v))
+            } else {
+                s.parse::<f64>()
+                    .map(LuaValue::Number)
+                    .map_err(|_| format!("invalid number `{}`", s))
+            }
+        }
+
+        fn string(&mut self) -> Result<String, String> {
+            let quote = self.src[self.pos];
+            self.pos += 1;
+            let mut bytes = Vec::new();
+            loop {
+                let c = self.peek().ok_or("unterminated string")?;
+                self.pos += 1;
+                match c {
+                    c if c == quote => break,
+                    b'\n' | b'\r' => return Err(String::from("unescaped newline")),
+                    b'\\' => {
+                        let e = self.peek().ok_or("unterminated escape")?;
+                        self.pos += 1;
+                        match e {
+                            b'n' => bytes.push(b'\n'),
+                            b'r' => bytes.push(b'\r'),
+                            b't' => bytes.push(b'\t'),
+                            b'a' => bytes.push(0x07),
+                            b'b' => bytes.push(0x08),
+                            b'f' => bytes.push(0x0c),
+                            b'v' => bytes.push(0x0b),
+                            b'\\' | b'\'' | b'"' | b'\n' => bytes.push(e),
+                            b'0'..=b'9' => {
+                                let mut n = u32::from(e - b'0');
+                                for _ in 0..2 {
+                                    match self.peek() {
+                                        Some(d @ b'0'..=b'9') => {
+                                            n = n * 10 + u32::from(d - b'0');
+                                            self.pos += 1;
+                                        }
+                                        _ => break,
+                                    }
+                                }
+                                bytes.push(u8::try_from(n).map_err(|_| "escape too large")?);
+                            }
+                            _ => return Err(format!("invalid escape `\\{}`", e as char)),
+                        }
+                    }
+                    c => bytes.push(c),
+                }
+            }
+            String::from_utf8(bytes).map_err(|err| err.to_string())
+        }
+
+        /// bare `key=` (restores the position when not found).
+        fn key(&mut self) -> Option<String> {
+            let start = self.pos;
+            while self
+                .peek()
+                .is_some_and(|c| c.is_ascii_alphanumeric() || c == b'_')
+            {
+                self.pos += 1;
+            }
+            let k = String::from_utf8(self.src[start..self.pos].to_vec()).unwrap();
+            if !k.is_empty() && self.eat("=") {
+                Some(k)
+            } else {
+                self.pos = start;
+                None
+            }
+        }
+
+        fn table(&mut self) -> Result<LuaValue, String> {
+            self.pos += 1;
+            let mut items = Vec::new();
+            let mut fields = Vec::new();
+            while !self.eat("}") {
+                if self.eat("[") {
+                    let k = self.string()?;
+                    if !self.eat("]=") {
+                        return Err(String::from("expected `]=`"));
+                    }
+                    fields.push((k, self.value()?));
+                } else if let Some(k) = self.key() {
+                    fields.push((k, self.value()?));
+                } else {
+                    items.push(self.value()?);
+                }
+                if !self.eat(",") {
+                    return Err(String::from("expected `,`"));
+                }
+            }
+            match (items.is_empty(), fields.is_empty()) {
+                (_, true) => Ok(LuaValue::Array(items)),
+                (true, false) => Ok(LuaValue::Table(fields)),
+                _ => Err(String::from("mixed table")),
+            }
+        }
+    }
+
+    fn lua_value() -> impl Strategy<Value = LuaValue> {
+        let leaf = prop_oneof![
+            Just(LuaValue::Nil),
+            any::<bool>().prop_map(LuaValue::Bool),
+            any::<i64>().prop_map(LuaValue::Integer),
+            any::<String>().prop_map(LuaValue::Str),
+        ];
+        leaf.prop_recursive(4, 64, 8, |inner| {
+            prop_oneof![
+                prop::collection::vec(inner.clone(), 0..8).prop_map(LuaValue::Array),
+                // an empty record is indistinguishable from an empty array.
+                prop::collection::vec((any::<String>(), inner), 1..8).prop_map(LuaValue::Table),
+            ]
+        })
+    }
+
+    #[rstest(
+        arg,
+        exp,
+        case("foo", "'foo'"),
+        case("", "''"),
+        case("it's", r"'it\'s'"),
+        case(r"C:\path", r"'C:\\path'"),
+        case("a\nb\r\tc", r"'a\nb\r\tc'"),
+        case("\0", r"'\000'"),
+        case("\x1b1", r"'\0271'"),
+        case("]]", "']]'"),
+        case("あ", "'あ'")
+    )]
+    fn quote_string(arg: &str, exp: &str) {
+        // act:
+        let act = quote(arg);
+
+        // assert:
+        assert_eq!(act, exp);
+    }
+
+    #[rstest(arg, exp,
+        case(LuaValue::Nil, "nil"),
+        case(LuaValue::from(true), "true"),
+        case(LuaValue::from(-1), "-1"),
+        case(LuaValue::from(0.5), "0.5"),
+        case(LuaValue::from(f64::INFINITY), "1/0"),
+        case(LuaValue::from(None::<&str>), "nil"),
+        case(LuaValue::from(&["foo", "bar"][..]), "{'foo','bar',}"),
+        case(LuaValue::Array(vec![LuaValue::Array(vec![]), LuaValue::from(1)]), "{{},1,}"),
+        case(
+            LuaValue::Table(vec![
+                (String::from("lhs"), LuaValue::from("<leader>f")),
+                (String::from("end"), LuaValue::from(false)),
+                (String::from("a-b"), LuaValue::from(1)),
+            ]),
+            "{lhs='<leader>f',['end']=false,['a-b']=1,}"
+        ),
+    )]
+    fn value_to_lua(arg: LuaValue, exp: &str) {
+        // act:
+        let act = arg.to_string();
+
+        // assert:
+        assert_eq!(act, exp);
+    }
+
+    proptest! {
+        #[test]
+        fn roundtrip_string(s in any::<String>()) {
+            // arrange:
+            let quoted = quote(&s);
+
+            // act:
+            let act = Parser::parse(&quoted);
+
+            // assert:
+            // the lexer of `validate_syntax` (not the parser above) accepts the literal.
+            prop_assert_eq!(syntax::check(&format!("return {}", quoted)), Ok(()));
+            prop_assert_eq!(act, Ok(LuaValue::Str(s)));
+        }
+
+        #[test]
+        fn quoted_string_is_single_line(s in any::<String>()) {
+            // act:
+            let act = quote(&s);
+
+            // assert:
+            prop_assert!(!act.contains('\n') && !act.contains('\r'));
+        }
+
+        #[test]
+        fn roundtrip_value(v in lua_value()) {
+            // arrange:
+            let code = v.to_string();
+
+            // act:
+            let act = Parser::parse(&code);
+
+            // assert:
+            prop_assert_eq!(syntax::check(&format!("return {}", code)), Ok(()));
+            prop_assert_eq!(act, Ok(v));
+        }
+    }
+}
@@ -0,0 +1,261 @@
+use crate::generator::output::{replace_dir, Memory, Output};
+use anyhow::{bail, Context, Result};
+use log::debug;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// file name of the manifest (`sha256sum` format) written by incremental generation.
+pub const MANIFEST_NAME: &str = ".manifest";
+
+/// path -> content hash.
+type Manifest = BTreeMap<String, String>;
+
+/// files touched by incremental generation.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Changes {
+    pub added: Vec<String>,
+    pub changed: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+impl Changes {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.changed.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// one line per file (e.g. `M deps/foo`).
+impl fmt::Display for Changes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (mark, paths) in [
+            ("A", &self.added),
+            ("M", &self.changed),
+            ("D", &self.removed),
+        ] {
+            for path in paths {
+                writeln!(f, "{} {}", mark, path)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// sha256 of `contents` in lower hex.
+fn hash(contents: &[u8]) -> String {
+    Sha256::digest(contents)
+        .iter()
+        .map(|x| format!("{:02x}", x))
+        .collect()
+}
+
+fn to_manifest(files: &BTreeMap<String, Vec<u8>>) -> Manifest {
+    files
+        .iter()
+        .map(|(path, contents)| (path.clone(), hash(contents)))
+        .collect()
+}
+
+fn format_manifest(manifest: &Manifest) -> String {
+    manifest
+        .iter()
+        .map(|(path, hash)| format!("{}  {}\n", hash, path))
+        .collect()
+}
+
+fn parse_manifest(text: &str) -> Result<Manifest> {
+    text.lines()
+        .enumerate()
+        .map(|(i, line)| match line.split_once("  ") {
+            Some((hash, path)) if !path.is_empty() => Ok((String::from(path), String::from(hash))),
+            _ => bail!("malformed line {}: `{}`", i + 1, line),
+        })
+        .collect()
+}
+
+/// manifest of the previous generation (`None` if `root` was not generated incrementally).
+fn read_manifest(root: &Path) -> Result<Option<Manifest>> {
+    let path = root.join(MANIFEST_NAME);
+    match fs::read_to_string(&path) {
+        Ok(text) => parse_manifest(&text)
+            .map(Some)
+            .with_context(|| format!("failed to parse `{}`", path.display())),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err).with_context(|| format!("failed to read `{}`", path.display())),
+    }
+}
+
+/// remove a file (and its directory if it is no longer generated and left empty).
+fn remove(root: &Path, path: &str, generated: &Memory) -> Result<()> {
+    let file = root.join(path);
+    match fs::remove_file(&file) {
+        Err(err) if err.kind() != io::ErrorKind::NotFound => {
+            return Err(err).with_context(|| format!("failed to remove `{}`", file.display()))
+        }
+        _ => (),
+    }
+    if let Some((dir, _)) = path.rsplit_once('/') {
+        if !generated.dirs.contains(dir) {
+            // fails if other files remain.
+            let _ = fs::remove_dir(root.join(dir));
+        }
+    }
+    Ok(())
+}
+
+/// bring `root` up to date with `generated`, rewriting only files whose content differs on disk.
+///
+/// files edited by hand are restored, files dropped from the manifest are removed.
+/// a directory without a manifest is replaced as a whole (see [`replace_dir`]).
+pub fn sync_dir(root: &Path, generated: &Memory) -> Result<Changes> {
+    let manifest = to_manifest(&generated.files);
+    let Some(prev) = read_manifest(root)? else {
+        replace_dir(root, |out| {
+            for dir in generated.dirs.iter() {
+                out.create_dir(dir)?;
+            }
+            for (path, contents) in generated.files.iter() {
+                out.write(path, contents)?;
+            }
+            out.write(MANIFEST_NAME, format_manifest(&manifest).as_bytes())
+        })?;
+        return Ok(Changes {
+            added: manifest.into_keys().collect(),
+            ..Default::default()
+        });
+    };
+
+    let mut changes = Changes::default();
+    for dir in generated.dirs.iter() {
+        let path = root.join(dir);
+        fs::create_dir_all(&path)
+            .with_context(|| format!("failed to create `{}`", path.display()))?;
+    }
+    for (path, contents) in generated.files.iter() {
+        let file = root.join(path);
+        match prev.get(path) {
+            None => changes.added.push(path.clone()),
+            // compare with the disk rather than the manifest to restore edited files.
+            Some(_) if fs::read(&file).is_ok_and(|x| x == *contents) => continue,
+            Some(_) => changes.changed.push(path.clone()),
+        }
+        fs::write(&file, contents)
+            .with_context(|| format!("failed to write `{}`", file.display()))?;
+        debug!(path = path; "write");
+    }
+    for path in prev.keys().filter(|x| !manifest.contains_key(*x)) {
+        remove(root, path, generated)?;
+        debug!(path = path; "remove");
+        changes.removed.push(path.clone());
+    }
+
+    if manifest != prev {
+        let path = root.join(MANIFEST_NAME);
+        fs::write(&path, format_manifest(&manifest))
+            .with_context(|| format!("failed to write `{}`", path.display()))?;
+    }
+    Ok(changes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use tempfile::tempdir;
+
+    fn memory(dirs: &[&str], files: &[(&str, &str)]) -> Memory {
+        let mut out = Memory::default();
+        for dir in dirs {
+            out.create_dir(dir).unwrap();
+        }
+        for (path, contents) in files {
+            out.write(path, contents.as_bytes()).unwrap();
+        }
+        out
+    }
+
+    #[test]
+    fn manifest_roundtrip() {
+        // arrange:
+        let files = memory(&["mods"], &[("lazy", "return {}"), ("mods/foo", "")]).files;
+
+        // act:
+        let act = format_manifest(&to_manifest(&files));
+
+        // assert:
+        assert_eq!(
+            act,
+            "7df8f0cd9e1cd0b204a760783671c0ccca4a3258dc7c51f59e9e2295c6c25315  lazy\n\
+             e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855  mods/foo\n"
+        );
+        assert_eq!(parse_manifest(&act).unwrap(), to_manifest(&files));
+    }
+
+    #[rstest(text, case("deadbeef lazy"), case("deadbeef  "))]
+    fn manifest_malformed(text: &str) {
+        // act:
+        let act = parse_manifest(text).unwrap_err();
+
+        // assert:
+        assert_eq!(act.to_string(), format!("malformed line 1: `{}`", text));
+    }
+
+    #[test]
+    fn sync_first_time() {
+        // arrange:
+        let tmp = tempdir().unwrap();
+        let root = tmp.path().join("root");
+        let generated = memory(&["mods"], &[("lazy", "return {}"), ("mods/foo", "a")]);
+
+        // act:
+        let act = sync_dir(&root, &generated).unwrap();
+
+        // assert:
+        assert_eq!(act.added, vec!["lazy", "mods/foo"]);
+        assert!(act.changed.is_empty() && act.removed.is_empty());
+        assert_eq!(fs::read_to_string(root.join("mods/foo")).unwrap(), "a");
+        assert!(root.join(MANIFEST_NAME).is_file());
+    }
+
+    #[test]
+    fn sync_incremental() {
+        // arrange:
+        let tmp = tempdir().unwrap();
+        let root = tmp.path().join("root");
+        let prev = memory(
+            &["mods", "cmds"],
+            &[("lazy", "return {}"), ("mods/foo", "a"), ("cmds/Foo", "b")],
+        );
+        sync_dir(&root, &prev).unwrap();
+        // files edited by hand must be restored.
+        fs::write(root.join("lazy"), "edited").unwrap();
+        let generated = memory(
+            &["mods"],
+            &[("lazy", "return {}"), ("mods/foo", "c"), ("mods/bar", "d")],
+        );
+
+        // act:
+        let act = sync_dir(&root, &generated).unwrap();
+
+        // assert:
+        assert_eq!(
+            act,
+            Changes {
+                added: vec![String::from("mods/bar")],
+                changed: vec![String::from("lazy"), String::from("mods/foo")],
+                removed: vec![String::from("cmds/Foo")],
+            }
+        );
+        assert_eq!(
+            act.to_string(),
+            "A mods/bar\nM lazy\nM mods/foo\nD cmds/Foo\n"
+        );
+        assert_eq!(fs::read_to_string(root.join("lazy")).unwrap(), "return {}");
+        assert_eq!(fs::read_to_string(root.join("mods/foo")).unwrap(), "c");
+        assert!(!root.join("cmds").exists());
+        assert!(sync_dir(&root, &generated).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,367 @@
+use anyhow::{ensure, Context, Result};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use tar::{Builder, EntryType, Header};
+
+/// destination of generated files (paths are relative to the root, separated by `/`).
+pub trait Output {
+    /// create a directory.
+    fn create_dir(&mut self, path: &str) -> Result<()>;
+    /// write a file.
+    fn write(&mut self, path: &str, contents: &[u8]) -> Result<()>;
+}
+
+/// write into an (existing) directory.
+#[derive(Debug, Clone)]
+pub struct Dir {
+    root: PathBuf,
+}
+
+impl Dir {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Dir { root: root.into() }
+    }
+}
+
+impl fmt::Display for Dir {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.root.display())
+    }
+}
+
+impl Output for Dir {
+    fn create_dir(&mut self, path: &str) -> Result<()> {
+        let path = self.root.join(path);
+        fs::create_dir(&path).with_context(|| format!("failed to create `{}`", path.display()))
+    }
+
+    fn write(&mut self, path: &str, contents: &[u8]) -> Result<()> {
+        let path = self.root.join(path);
+        fs::write(&path, contents).with_context(|| format!("failed to write `{}`", path.display()))
+    }
+}
+
+/// file marking a directory written by [`replace_dir`], which may be replaced as a whole.
+pub const MARKER_NAME: &str = ".oboro";
+
+/// write into `root` and replace whatever it contained.
+///
+/// an existing non-empty `root` is written to a temporary sibling first and swapped in on success,
+/// so a failure leaves it untouched and files no longer generated are removed.
+/// a non-empty `root` not written by a previous generation (without [`MARKER_NAME`]) is refused.
+pub fn replace_dir(root: &Path, write: impl FnOnce(&mut Dir) -> Result<()>) -> Result<()> {
+    let is_empty = match fs::read_dir(root) {
+        Ok(mut entries) => entries.next().is_none(),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {
+            fs::create_dir_all(root)
+                .with_context(|| format!("failed to create `{}`", root.display()))?;
+            true
+        }
+        Err(err) => {
+            return Err(err).with_context(|| format!("failed to read `{}`", root.display()))
+        }
+    };
+    let write = |dir: &Path| {
+        let mut out = Dir::new(dir);
+        out.write(MARKER_NAME, b"")?;
+        write(&mut out)
+    };
+    if is_empty {
+        return write(root);
+    }
+    ensure!(
+        root.join(MARKER_NAME).is_file(),
+        "refused to replace `{}`: it is not empty and was not generated by oboro (no `{}`).",
+        root.display(),
+        MARKER_NAME
+    );
+
+    // `.` has no name to derive siblings from.
+    let root = &root
+        .canonicalize()
+        .with_context(|| format!("failed to resolve `{}`", root.display()))?;
+    let sibling = |suffix: &str| {
+        let name = root.file_name().unwrap_or_default().to_string_lossy();
+        root.with_file_name(format!(".{}.{}-{}", name, suffix, process::id()))
+    };
+    let tmp = sibling("tmp");
+    let old = sibling("old");
+    if tmp.exists() {
+        fs::remove_dir_all(&tmp)
+            .with_context(|| format!("failed to remove `{}`", tmp.display()))?;
+    }
+    fs::create_dir(&tmp).with_context(|| format!("failed to create `{}`", tmp.display()))?;
+    if let Err(err) = write(&tmp) {
+        let _ = fs::remove_dir_all(&tmp);
+        return Err(err);
+    }
+
+    if let Err(err) = fs::rename(root, &old) {
+        let _ = fs::remove_dir_all(&tmp);
+        return Err(err).with_context(|| format!("failed to move `{}`", root.display()));
+    }
+    if let Err(err) = fs::rename(&tmp, root) {
+        let _ = fs::rename(&old, root);
+        let _ = fs::remove_dir_all(&tmp);
+        return Err(err).with_context(|| format!("failed to move `{}`", tmp.display()));
+    }
+    fs::remove_dir_all(&old).with_context(|| format!("failed to remove `{}`", old.display()))
+}
+
+/// keep generated files in memory.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Memory {
+    pub dirs: BTreeSet<String>,
+    /// path -> contents.
+    pub files: BTreeMap<String, Vec<u8>>,
+}
+
+impl Memory {
+    /// contents of `path` as a string.
+    pub fn read(&self, path: &str) -> Option<&str> {
+        self.files
+            .get(path)
+            .and_then(|x| std::str::from_utf8(x).ok())
+    }
+}
+
+impl fmt::Display for Memory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<memory>")
+    }
+}
+
+impl Output for Memory {
+    fn create_dir(&mut self, path: &str) -> Result<()> {
+        self.dirs.insert(String::from(path));
+        Ok(())
+    }
+
+    fn write(&mut self, path: &str, contents: &[u8]) -> Result<()> {
+        self.files.insert(String::from(path), contents.to_vec());
+        Ok(())
+    }
+}
+
+/// write into a tar archive (entries have fixed owner, mode and mtime to be reproducible).
+pub struct Tar<W: Write> {
+    builder: Builder<W>,
+}
+
+impl<W: Write> Tar<W> {
+    pub fn new(writer: W) -> Self {
+        Tar {
+            builder: Builder::new(writer),
+        }
+    }
+
+    /// finish the archive and return the writer.
+    pub fn finish(self) -> Result<W> {
+        self.builder
+            .into_inner()
+            .context("failed to finish the archive")
+    }
+
+    fn append(&mut self, path: &str, entry_type: EntryType, mode: u32, data: &[u8]) -> Result<()> {
+        let mut header = Header::new_gnu();
+        header.set_entry_type(entry_type);
+        header.set_mode(mode);
+        header.set_mtime(0);
+        header.set_uid(0);
+        header.set_gid(0);
+        header.set_size(data.len() as u64);
+        self.builder
+            .append_data(&mut header, path, data)
+            .with_context(|| format!("failed to append `{}`", path))
+    }
+}
+
+impl<W: Write> Output for Tar<W> {
+    fn create_dir(&mut self, path: &str) -> Result<()> {
+        self.append(&format!("{}/", path), EntryType::Directory, 0o755, &[])
+    }
+
+    fn write(&mut self, path: &str, contents: &[u8]) -> Result<()> {
+        self.append(path, EntryType::Regular, 0o644, contents)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::bail;
+    use std::io::Read;
+    use tar::Archive;
+    use tempfile::tempdir;
+
+    fn list(root: &Path) -> Vec<String> {
+        let mut names = fs::read_dir(root)
+            .unwrap()
+            .map(|x| x.unwrap().file_name().to_string_lossy().into_owned())
+            .collect::<Vec<_>>();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn write_tar() {
+        // arrange:
+        let mut tar = Tar::new(Vec::new());
+
+        // act:
+        tar.create_dir("mods").unwrap();
+        tar.write("mods/foo", b"return {'foo',}").unwrap();
+        tar.write("lazy", b"return {}").unwrap();
+        let act = tar.finish().unwrap();
+
+        // assert:
+        let mut archive = Archive::new(&act[..]);
+        let entries = archive
+            .entries()
+            .unwrap()
+            .map(|entry| {
+                let mut entry = entry.unwrap();
+                let mut contents = String::new();
+                entry.read_to_string(&mut contents).unwrap();
+                (
+                    entry.path().unwrap().to_string_lossy().into_owned(),
+                    entry.header().entry_type(),
+                    entry.header().mtime().unwrap(),
+                    contents,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            entries,
+            vec![
+                (
+                    String::from("mods/"),
+                    EntryType::Directory,
+                    0,
+                    String::new()
+                ),
+                (
+                    String::from("mods/foo"),
+                    EntryType::Regular,
+                    0,
+                    String::from("return {'foo',}")
+                ),
+                (
+                    String::from("lazy"),
+                    EntryType::Regular,
+                    0,
+                    String::from("return {}")
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn write_memory() {
+        // arrange:
+        let mut memory = Memory::default();
+
+        // act:
+        memory.create_dir("mods").unwrap();
+        memory.write("mods/foo", b"return {'foo',}").unwrap();
+
+        // assert:
+        assert_eq!(memory.dirs, BTreeSet::from([String::from("mods")]));
+        assert_eq!(memory.read("mods/foo"), Some("return {'foo',}"));
+        assert_eq!(memory.read("lazy"), None);
+    }
+
+    #[test]
+    fn replace_dir_in_place() {
+        // arrange:
+        let tmp = tempdir().unwrap();
+        let root = tmp.path().join("root");
+        replace_dir(&root, |out| {
+            out.create_dir("mods")?;
+            out.write("mods/stale", b"return {'stale',}")?;
+            out.write("lazy", b"return {'stale',}")
+        })
+        .unwrap();
+
+        // act:
+        replace_dir(&root, |out| {
+            out.create_dir("mods")?;
+            out.write("lazy", b"return {}")
+        })
+        .unwrap();
+
+        // assert:
+        assert_eq!(list(&root), vec![MARKER_NAME, "lazy", "mods"]);
+        assert!(list(&root.join("mods")).is_empty());
+        assert_eq!(fs::read_to_string(root.join("lazy")).unwrap(), "return {}");
+        assert_eq!(list(tmp.path()), vec!["root"]);
+    }
+
+    #[test]
+    fn replace_dir_failure() {
+        // arrange:
+        let tmp = tempdir().unwrap();
+        let root = tmp.path().join("root");
+        replace_dir(&root, |out| out.write("lazy", b"return {}")).unwrap();
+
+        // act:
+        let act = replace_dir(&root, |out| {
+            out.write("lazy", b"return {'half',}")?;
+            bail!("failed")
+        });
+
+        // assert:
+        assert!(act.is_err());
+        assert_eq!(fs::read_to_string(root.join("lazy")).unwrap(), "return {}");
+        assert_eq!(list(tmp.path()), vec!["root"]);
+    }
+
+    #[test]
+    fn replace_dir_not_generated() {
+        // arrange:
+        let tmp = tempdir().unwrap();
+        let root = tmp.path().join("root");
+        fs::create_dir(&root).unwrap();
+        fs::write(root.join("precious.txt"), "precious").unwrap();
+
+        // act:
+        let act = replace_dir(&root, |out| out.write("lazy", b"return {}")).unwrap_err();
+
+        // assert:
+        assert_eq!(
+            act.to_string(),
+            format!(
+                "refused to replace `{}`: it is not empty and was not generated by oboro (no `.oboro`).",
+                root.display()
+            )
+        );
+        assert_eq!(list(&root), vec!["precious.txt"]);
+        assert_eq!(list(tmp.path()), vec!["root"]);
+    }
+
+    #[test]
+    fn replace_dir_move_failure() {
+        // arrange:
+        let tmp = tempdir().unwrap();
+        let root = tmp.path().join("root");
+        replace_dir(&root, |out| out.write("lazy", b"return {}")).unwrap();
+        // moving `root` aside fails as its destination is a non-empty directory.
+        let old = tmp.path().join(format!(".root.old-{}", process::id()));
+        fs::create_dir(&old).unwrap();
+        fs::write(old.join("file"), "").unwrap();
+
+        // act:
+        let act = replace_dir(&root, |out| out.write("lazy", b"return {'new',}"));
+
+        // assert:
+        assert!(act.is_err());
+        assert_eq!(fs::read_to_string(root.join("lazy")).unwrap(), "return {}");
+        let mut exp = vec![format!(".root.old-{}", process::id()), String::from("root")];
+        exp.sort();
+        assert_eq!(list(tmp.path()), exp);
+    }
+}
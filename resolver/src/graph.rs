@@ -0,0 +1,415 @@
+use crate::config::output::OboroConfig;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+/// kind of a graph node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum NodeKind {
+    StartPlugin,
+    OptPlugin,
+    Bundle,
+    Module,
+    Event,
+    Filetype,
+    Command,
+    Key,
+    Lazy,
+}
+
+impl NodeKind {
+    fn is_trigger(self) -> bool {
+        !matches!(
+            self,
+            NodeKind::StartPlugin | NodeKind::OptPlugin | NodeKind::Bundle
+        )
+    }
+}
+
+/// kind of a graph edge.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum EdgeKind {
+    /// plugin (or bundle) -> its dependency.
+    Depends,
+    /// bundle -> its plugin.
+    Contains,
+    /// trigger -> plugin (or bundle) loaded by it.
+    Loads,
+}
+
+impl EdgeKind {
+    fn label(self) -> &'static str {
+        match self {
+            EdgeKind::Depends => "depends",
+            EdgeKind::Contains => "contains",
+            EdgeKind::Loads => "loads",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Node {
+    /// unique id (plugin or bundle id, `<kind>:<name>` for triggers).
+    pub id: String,
+    pub label: String,
+    pub kind: NodeKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Edge {
+    pub from: String,
+    pub to: String,
+    pub kind: EdgeKind,
+}
+
+/// plugin/bundle dependency graph with trigger edges.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Graph {
+    pub nodes: Vec<Node>,
+    pub edges: Vec<Edge>,
+}
+
+impl Graph {
+    fn node(&mut self, id: String, label: String, kind: NodeKind) {
+        self.nodes.push(Node { id, label, kind });
+    }
+
+    fn edge(&mut self, from: &str, to: &str, kind: EdgeKind) {
+        self.edges.push(Edge {
+            from: String::from(from),
+            to: String::from(to),
+            kind,
+        });
+    }
+
+    /// add a trigger node and its edges to the loaded ids.
+    fn trigger(&mut self, kind: NodeKind, prefix: &str, name: &str, ids: &[&str]) {
+        let id = format!("{}:{}", prefix, name);
+        for to in ids {
+            self.edge(&id, to, EdgeKind::Loads);
+        }
+        self.node(id, format!("{} {}", prefix, name), kind);
+    }
+
+    /// build the graph of the resolved config.
+    pub fn new(config: &OboroConfig) -> Self {
+        let mut graph = Graph::default();
+
+        for plugin in config.startup_plugins.iter() {
+            graph.node(
+                String::from(plugin.id),
+                String::from(plugin.id),
+                NodeKind::StartPlugin,
+            );
+        }
+        for plugin in config.lazy_plugins.iter() {
+            graph.node(
+                String::from(plugin.id),
+                String::from(plugin.id),
+                NodeKind::OptPlugin,
+            );
+            for dep in plugin.deps.iter().chain(plugin.dep_bundles.iter()) {
+                graph.edge(plugin.id, dep, EdgeKind::Depends);
+            }
+        }
+        for bundle in config.bundles.iter() {
+            graph.node(
+                String::from(bundle.id),
+                String::from(bundle.id),
+                NodeKind::Bundle,
+            );
+            for plugin in bundle.plugins.iter() {
+                graph.edge(bundle.id, plugin, EdgeKind::Contains);
+            }
+            for dep in bundle.deps.iter().chain(bundle.dep_bundles.iter()) {
+                graph.edge(bundle.id, dep, EdgeKind::Depends);
+            }
+        }
+
+        for (name, ids) in config.mod_map.iter() {
+            graph.trigger(NodeKind::Module, "module", name, ids);
+        }
+        for ((ev, pattern), ids) in config.ev_map.iter() {
+            graph.trigger(
+                NodeKind::Event,
+                "event",
+                &format!("{} {}", ev, pattern),
+                ids,
+            );
+        }
+        for (name, ids) in config.ft_map.iter() {
+            graph.trigger(NodeKind::Filetype, "filetype", name, ids);
+        }
+        for (name, ids) in config.cmd_map.iter() {
+            graph.trigger(NodeKind::Command, "command", name, ids);
+        }
+        for ((mode, lhs), ids) in config.key_map.iter() {
+            graph.trigger(NodeKind::Key, "key", &format!("{} {}", mode, lhs), ids);
+        }
+        if !config.lazys.is_empty() {
+            for to in config.lazys.iter() {
+                graph.edge("lazy:timer", to, EdgeKind::Loads);
+            }
+            graph.node(
+                String::from("lazy:timer"),
+                String::from("lazy"),
+                NodeKind::Lazy,
+            );
+        }
+
+        graph
+    }
+
+    /// graphviz dot.
+    pub fn to_dot(&self) -> String {
+        let quote = |s: &str| format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""));
+        let mut out = String::from("digraph oboro {\n  rankdir=LR;\n");
+        for node in self.nodes.iter() {
+            let shape = match node.kind {
+                NodeKind::StartPlugin => "box, style=filled",
+                NodeKind::OptPlugin => "box",
+                NodeKind::Bundle => "box3d",
+                _ => "ellipse",
+            };
+            let _ = writeln!(
+                out,
+                "  {} [label={}, shape={}];",
+                quote(&node.id),
+                quote(&node.label),
+                shape
+            );
+        }
+        for edge in self.edges.iter() {
+            let style = match edge.kind {
+                EdgeKind::Depends => "solid",
+                EdgeKind::Contains => "dashed",
+                EdgeKind::Loads => "dotted",
+            };
+            let _ = writeln!(
+                out,
+                "  {} -> {} [label={}, style={}];",
+                quote(&edge.from),
+                quote(&edge.to),
+                edge.kind.label(),
+                style
+            );
+        }
+        out + "}\n"
+    }
+
+    /// mermaid flowchart.
+    pub fn to_mermaid(&self) -> String {
+        // mermaid ids must be plain words, so number the nodes.
+        let index = self
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(i, node)| (node.id.as_str(), format!("n{}", i)))
+            .collect::<BTreeMap<_, _>>();
+        let label = |s: &str| s.replace('"', "#quot;");
+        let mut out = String::from("flowchart LR\n");
+        for node in self.nodes.iter() {
+            let (open, close) = match node.kind {
+                NodeKind::Bundle => ("[[", "]]"),
+                kind if kind.is_trigger() => ("([", "])"),
+                _ => ("[", "]"),
+            };
+            let _ = writeln!(
+                out,
+                "  {}{}\"{}\"{}",
+                index[node.id.as_str()],
+                open,
+                label(&node.label),
+                close
+            );
+        }
+        for edge in self.edges.iter() {
+            let arrow = match edge.kind {
+                EdgeKind::Depends => "-->",
+                EdgeKind::Contains => "-.->",
+                EdgeKind::Loads => "==>",
+            };
+            // edges to unknown ids are rejected by resolve, so every end is a node.
+            let _ = writeln!(
+                out,
+                "  {} {}|{}| {}",
+                index[edge.from.as_str()],
+                arrow,
+                edge.kind.label(),
+                index[edge.to.as_str()]
+            );
+        }
+        out
+    }
+
+    /// json (`{"nodes": [...], "edges": [...]}`).
+    pub fn to_json(&self) -> String {
+        // plain structs always serialize.
+        serde_json::to_string_pretty(self).unwrap() + "\n"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::output::{Bundle, LazyPlugin, StartupPlugin};
+
+    fn config() -> OboroConfig<'static> {
+        OboroConfig {
+            startup_plugins: vec![StartupPlugin {
+                id: "foo",
+                ..Default::default()
+            }],
+            lazy_plugins: vec![
+                LazyPlugin {
+                    id: "bar",
+                    deps: vec!["baz"],
+                    ..Default::default()
+                },
+                LazyPlugin {
+                    id: "baz",
+                    ..Default::default()
+                },
+            ],
+            bundles: vec![Bundle {
+                id: "hoge",
+                plugins: vec!["baz"],
+                ..Default::default()
+            }],
+            ev_map: BTreeMap::from([(("User", "VeryLazy"), vec!["bar"])]),
+            ft_map: BTreeMap::from([("rust", vec!["hoge"])]),
+            lazys: vec!["baz"],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn build_graph() {
+        // act:
+        let act = Graph::new(&config());
+
+        // assert:
+        assert_eq!(
+            act.nodes
+                .iter()
+                .map(|x| (x.id.as_str(), x.kind))
+                .collect::<Vec<_>>(),
+            vec![
+                ("foo", NodeKind::StartPlugin),
+                ("bar", NodeKind::OptPlugin),
+                ("baz", NodeKind::OptPlugin),
+                ("hoge", NodeKind::Bundle),
+                ("event:User VeryLazy", NodeKind::Event),
+                ("filetype:rust", NodeKind::Filetype),
+                ("lazy:timer", NodeKind::Lazy),
+            ]
+        );
+        assert_eq!(
+            act.edges
+                .iter()
+                .map(|x| (x.from.as_str(), x.to.as_str(), x.kind))
+                .collect::<Vec<_>>(),
+            vec![
+                ("bar", "baz", EdgeKind::Depends),
+                ("hoge", "baz", EdgeKind::Contains),
+                ("event:User VeryLazy", "bar", EdgeKind::Loads),
+                ("filetype:rust", "hoge", EdgeKind::Loads),
+                ("lazy:timer", "baz", EdgeKind::Loads),
+            ]
+        );
+    }
+
+    #[test]
+    fn graph_to_dot() {
+        // arrange:
+        let mut config = config();
+        config.cmd_map = BTreeMap::from([("Say\"hi\"", vec!["bar"])]);
+        let graph = Graph::new(&config);
+
+        // act:
+        let act = graph.to_dot();
+
+        // assert:
+        assert_eq!(
+            act,
+            r#"digraph oboro {
+  rankdir=LR;
+  "foo" [label="foo", shape=box, style=filled];
+  "bar" [label="bar", shape=box];
+  "baz" [label="baz", shape=box];
+  "hoge" [label="hoge", shape=box3d];
+  "event:User VeryLazy" [label="event User VeryLazy", shape=ellipse];
+  "filetype:rust" [label="filetype rust", shape=ellipse];
+  "command:Say\"hi\"" [label="command Say\"hi\"", shape=ellipse];
+  "lazy:timer" [label="lazy", shape=ellipse];
+  "bar" -> "baz" [label=depends, style=solid];
+  "hoge" -> "baz" [label=contains, style=dashed];
+  "event:User VeryLazy" -> "bar" [label=loads, style=dotted];
+  "filetype:rust" -> "hoge" [label=loads, style=dotted];
+  "command:Say\"hi\"" -> "bar" [label=loads, style=dotted];
+  "lazy:timer" -> "baz" [label=loads, style=dotted];
+}
+"#
+        );
+    }
+
+    #[test]
+    fn graph_to_mermaid() {
+        // arrange:
+        let graph = Graph::new(&config());
+
+        // act:
+        let act = graph.to_mermaid();
+
+        // assert:
+        assert_eq!(
+            act,
+            r#"flowchart LR
+  n0["foo"]
+  n1["bar"]
+  n2["baz"]
+  n3[["hoge"]]
+  n4(["event User VeryLazy"])
+  n5(["filetype rust"])
+  n6(["lazy"])
+  n1 -->|depends| n2
+  n3 -.->|contains| n2
+  n4 ==>|loads| n1
+  n5 ==>|loads| n3
+  n6 ==>|loads| n2
+"#
+        );
+    }
+
+    #[test]
+    fn graph_to_json() {
+        // arrange:
+        let graph = Graph::new(&OboroConfig {
+            lazy_plugins: vec![LazyPlugin {
+                id: "bar",
+                ..Default::default()
+            }],
+            lazys: vec!["bar"],
+            ..Default::default()
+        });
+
+        // act:
+        let act = serde_json::from_str::<serde_json::Value>(&graph.to_json()).unwrap();
+
+        // assert:
+        assert_eq!(
+            act,
+            serde_json::json!({
+                "nodes": [
+                    { "id": "bar", "label": "bar", "kind": "optPlugin" },
+                    { "id": "lazy:timer", "label": "lazy", "kind": "lazy" },
+                ],
+                "edges": [
+                    { "from": "lazy:timer", "to": "bar", "kind": "loads" },
+                ],
+            })
+        );
+    }
+}
@@ -0,0 +1,13 @@
+pub mod config;
+pub mod explain;
+pub mod generator;
+pub mod graph;
+
+pub use crate::config::discover::{discover_commands, discover_modules};
+pub use crate::config::input::OboroPluginConfig;
+pub use crate::config::output::OboroConfig;
+pub use crate::config::resolve;
+pub use crate::generator::manifest::{sync_dir, Changes};
+pub use crate::generator::output::{replace_dir, Dir, Memory, Output, Tar};
+pub use crate::generator::{generate, OutputMode};
+pub use crate::graph::Graph;
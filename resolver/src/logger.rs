@@ -0,0 +1,174 @@
+use anyhow::Result;
+use log::kv::{Key, Value, VisitSource};
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use serde_json::{Map, Value as JsonValue};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::sync::Mutex;
+
+/// level of records written to the json log.
+const JSON_LEVEL: LevelFilter = LevelFilter::Debug;
+
+/// collect key-values of a record.
+struct Collector(Vec<(String, String)>);
+
+impl<'kvs> VisitSource<'kvs> for Collector {
+    fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), log::kv::Error> {
+        self.0.push((key.to_string(), value.to_string()));
+        Ok(())
+    }
+}
+
+fn key_values(record: &Record) -> Vec<(String, String)> {
+    let mut collector = Collector(Vec::new());
+    // `Collector` never fails.
+    let _ = record.key_values().visit(&mut collector);
+    collector.0
+}
+
+/// human readable line (e.g. `[DEBUG] write path=/foo`).
+fn to_text(record: &Record) -> String {
+    key_values(record).into_iter().fold(
+        format!("[{}] {}", record.level(), record.args()),
+        |acc, (k, v)| acc + " " + &k + "=" + &v,
+    )
+}
+
+/// json line (e.g. `{"level":"debug","message":"write","path":"/foo"}`).
+fn to_json(record: &Record) -> JsonValue {
+    let mut map = Map::new();
+    map.insert(
+        String::from("level"),
+        JsonValue::from(record.level().as_str().to_lowercase()),
+    );
+    map.insert(
+        String::from("message"),
+        JsonValue::from(record.args().to_string()),
+    );
+    for (k, v) in key_values(record) {
+        map.insert(k, JsonValue::from(v));
+    }
+    JsonValue::Object(map)
+}
+
+/// logger writing to stderr and (optionally) to a json lines file.
+pub struct Logger {
+    level: LevelFilter,
+    json: Option<Mutex<BufWriter<File>>>,
+}
+
+impl Logger {
+    /// install the logger.
+    pub fn init(level: LevelFilter, json_path: Option<&str>) -> Result<()> {
+        let json = json_path
+            .map(File::create)
+            .transpose()?
+            .map(|file| Mutex::new(BufWriter::new(file)));
+        let max_level = if json.is_some() {
+            level.max(JSON_LEVEL)
+        } else {
+            level
+        };
+        log::set_boxed_logger(Box::new(Logger { level, json }))?;
+        log::set_max_level(max_level);
+        Ok(())
+    }
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level || (self.json.is_some() && metadata.level() <= JSON_LEVEL)
+    }
+
+    fn log(&self, record: &Record) {
+        if record.level() <= self.level {
+            eprintln!("{}", to_text(record));
+        }
+        if let Some(json) = &self.json {
+            if record.level() <= JSON_LEVEL {
+                if let Ok(mut writer) = json.lock() {
+                    let _ = writeln!(writer, "{}", to_json(record));
+                }
+            }
+        }
+    }
+
+    fn flush(&self) {
+        if let Some(json) = &self.json {
+            if let Ok(mut writer) = json.lock() {
+                let _ = writer.flush();
+            }
+        }
+    }
+}
+
+/// stderr level from command line flags.
+pub fn level_filter(verbose: bool, trace: bool) -> LevelFilter {
+    if trace {
+        Level::Trace.to_level_filter()
+    } else if verbose {
+        Level::Debug.to_level_filter()
+    } else {
+        Level::Warn.to_level_filter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[test]
+    fn record_to_text() {
+        // arrange:
+        let kvs = [("path", "/foo")];
+        let record = Record::builder()
+            .level(Level::Debug)
+            .args(format_args!("write"))
+            .key_values(&kvs)
+            .build();
+
+        // act:
+        let act = to_text(&record);
+
+        // assert:
+        assert_eq!(act, "[DEBUG] write path=/foo");
+    }
+
+    #[test]
+    fn record_to_json() {
+        // arrange:
+        let kvs = [("kind", "bundle"), ("id", "lsp")];
+        let record = Record::builder()
+            .level(Level::Debug)
+            .args(format_args!("resolve"))
+            .key_values(&kvs)
+            .build();
+
+        // act:
+        let act = to_json(&record).to_string();
+
+        // assert:
+        assert_eq!(
+            act,
+            r#"{"id":"lsp","kind":"bundle","level":"debug","message":"resolve"}"#
+        );
+    }
+
+    #[rstest(
+        verbose,
+        trace,
+        exp,
+        case(false, false, LevelFilter::Warn),
+        case(true, false, LevelFilter::Debug),
+        case(false, true, LevelFilter::Trace),
+        case(true, true, LevelFilter::Trace)
+    )]
+    fn flags_to_level(verbose: bool, trace: bool, exp: LevelFilter) {
+        // act:
+        let act = level_filter(verbose, trace);
+
+        // assert:
+        assert_eq!(act, exp);
+    }
+}
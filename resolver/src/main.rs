@@ -1,25 +1,180 @@
-mod config;
-mod generator;
-
-use crate::config::input::OboroPluginConfig;
-use crate::config::resolve;
-use crate::generator::generate;
-use std::{env, fs};
-
-fn main() {
-    println!("start");
-    let args: Vec<String> = env::args().collect();
-    let input_json_path = &args[1];
-    let output_dir = &args[2];
-    println!(
-        "input json: {}, output dir: {}",
-        input_json_path, output_dir
-    );
-
-    let input_json_text = fs::read_to_string(input_json_path).unwrap();
-    let config_src = serde_json::from_str::<OboroPluginConfig>(&input_json_text).unwrap();
-    let config = resolve(&config_src).unwrap();
-
-    generate(&config, output_dir).unwrap();
-    println!("completed!")
+mod logger;
+
+use crate::logger::{level_filter, Logger};
+use anyhow::{Context, Result};
+use clap::{Parser, Subcommand, ValueEnum};
+use log::info;
+use oboro_resolver::explain::explain;
+use oboro_resolver::{
+    discover_commands, discover_modules, generate, replace_dir, resolve, sync_dir, Graph, Memory,
+    OboroPluginConfig, OutputMode, Tar,
+};
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::process::ExitCode;
+
+/// resolve oboro-nvim plugin configuration.
+#[derive(Debug, Parser)]
+#[command(name = "oboro-resolver", version)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+    /// show what is resolved and written.
+    #[arg(short, long, global = true)]
+    verbose: bool,
+    /// show everything.
+    #[arg(long, global = true)]
+    trace: bool,
+    /// write a json lines log of what is resolved and written.
+    #[arg(long, global = true, value_name = "PATH")]
+    log_json: Option<String>,
+    /// add modules found in `lua/` of opt plugins to their module triggers.
+    #[arg(long, global = true)]
+    discover_modules: bool,
+    /// add user commands defined by opt plugins to their command triggers
+    /// (and report declared commands they do not define).
+    #[arg(long, global = true)]
+    discover_commands: bool,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// resolve config and generate the oboro root directory.
+    Generate {
+        /// input json (nix → rust).
+        input: String,
+        /// output directory, replaced if it was generated before (or archive with `--tar`).
+        out: String,
+        /// write a single lua module (`compiled.lua`) instead of a file tree.
+        #[arg(long)]
+        single_file: bool,
+        /// write a tar archive instead of a directory.
+        #[arg(long)]
+        tar: bool,
+        /// only rewrite files changed since the last incremental run and print what changed.
+        #[arg(long, conflicts_with = "tar")]
+        incremental: bool,
+    },
+    /// resolve and validate config without writing.
+    Check {
+        /// input json (nix → rust).
+        input: String,
+    },
+    /// explain how the plugin (or bundle) is configured and what loads it.
+    Explain {
+        /// input json (nix → rust).
+        input: String,
+        /// plugin id or bundle id.
+        id: String,
+    },
+    /// export the plugin/bundle dependency graph with its triggers.
+    Graph {
+        /// input json (nix → rust).
+        input: String,
+        /// output format.
+        #[arg(long, value_enum, default_value_t = GraphFormat::Dot)]
+        format: GraphFormat,
+    },
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum GraphFormat {
+    /// graphviz dot.
+    Dot,
+    /// mermaid flowchart.
+    Mermaid,
+    /// json (`{"nodes": [...], "edges": [...]}`).
+    Json,
+}
+
+/// read input json (and discover triggers if requested).
+fn read_input(path: &str, modules: bool, commands: bool) -> Result<OboroPluginConfig> {
+    let text = fs::read_to_string(path).with_context(|| format!("failed to read `{}`", path))?;
+    let mut config = serde_json::from_str::<OboroPluginConfig>(&text)
+        .with_context(|| format!("failed to parse `{}`", path))?;
+    if modules {
+        discover_modules(&mut config)?;
+    }
+    if commands {
+        discover_commands(&mut config)?;
+    }
+    Ok(config)
+}
+
+fn run(cli: Cli) -> Result<()> {
+    Logger::init(
+        level_filter(cli.verbose, cli.trace),
+        cli.log_json.as_deref(),
+    )?;
+
+    match cli.command {
+        Command::Generate {
+            input,
+            out,
+            single_file,
+            tar,
+            incremental,
+        } => {
+            let config_src = read_input(&input, cli.discover_modules, cli.discover_commands)?;
+            let config = resolve(&config_src)?;
+            let mode = if single_file {
+                OutputMode::SingleFile
+            } else {
+                OutputMode::Tree
+            };
+            if tar {
+                let file =
+                    File::create(&out).with_context(|| format!("failed to create `{}`", out))?;
+                let mut archive = Tar::new(BufWriter::new(file));
+                generate(&config, &mut archive, mode)?;
+                archive
+                    .finish()?
+                    .flush()
+                    .with_context(|| format!("failed to write `{}`", out))?;
+            } else if incremental {
+                let mut generated = Memory::default();
+                generate(&config, &mut generated, mode)?;
+                print!("{}", sync_dir(Path::new(&out), &generated)?);
+            } else {
+                replace_dir(Path::new(&out), |dir| generate(&config, dir, mode))?;
+            }
+            info!("generated {}.", out);
+            Ok(())
+        }
+        Command::Check { input } => {
+            let config_src = read_input(&input, cli.discover_modules, cli.discover_commands)?;
+            resolve(&config_src)?;
+            Ok(())
+        }
+        Command::Explain { input, id } => {
+            let config_src = read_input(&input, cli.discover_modules, cli.discover_commands)?;
+            let config = resolve(&config_src)?;
+            print!("{}", explain(&config, &id)?);
+            Ok(())
+        }
+        Command::Graph { input, format } => {
+            let config_src = read_input(&input, cli.discover_modules, cli.discover_commands)?;
+            let config = resolve(&config_src)?;
+            let graph = Graph::new(&config);
+            match format {
+                GraphFormat::Dot => print!("{}", graph.to_dot()),
+                GraphFormat::Mermaid => print!("{}", graph.to_mermaid()),
+                GraphFormat::Json => print!("{}", graph.to_json()),
+            }
+            Ok(())
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let result = run(Cli::parse());
+    log::logger().flush();
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {:#}", err);
+            ExitCode::FAILURE
+        }
+    }
 }
@@ -0,0 +1,53 @@
+use oboro_resolver::{generate, resolve, Memory, OboroPluginConfig, OutputMode};
+
+fn input() -> OboroPluginConfig {
+    serde_json::from_str(
+        r#"{
+            "startPlugins": [],
+            "optPlugins": [
+                { "id": "foo", "plugin": "foo", "startup": "", "priority": 0, "before": [], "after": [],
+                  "preConfig": "", "config": "require('foo').setup()", "deps": ["bar"], "depBundles": [],
+                  "mods": ["foo"], "evs": [], "fts": [], "cmds": ["Foo"], "keys": [], "lazy": false },
+                { "id": "bar", "plugin": "bar", "startup": "", "priority": 0, "before": [], "after": [],
+                  "preConfig": "", "config": "", "deps": [], "depBundles": [],
+                  "mods": [], "evs": [], "fts": [], "cmds": [], "keys": [], "lazy": false }
+            ],
+            "bundles": []
+        }"#,
+    )
+    .unwrap()
+}
+
+#[test]
+fn generate_in_memory() {
+    // arrange:
+    let src = input();
+    let mut out = Memory::default();
+
+    // act:
+    let config = resolve(&src).unwrap();
+    generate(&config, &mut out, OutputMode::Tree).unwrap();
+
+    // assert:
+    assert!(out.dirs.contains("cmds"));
+    assert_eq!(out.read("deps/foo"), Some("return {'bar',}"));
+    assert_eq!(out.read("cfgs/foo"), Some("require('foo').setup()"));
+    assert_eq!(out.read("mod_idx"), Some("return {foo={'foo',},}"));
+    assert_eq!(out.read("cmds/Foo"), Some("return {'foo',}"));
+}
+
+#[test]
+fn resolve_error() {
+    // arrange:
+    let mut src = input();
+    src.opt_plugins[1].config = String::from("if true then");
+
+    // act:
+    let act = resolve(&src).unwrap_err();
+
+    // assert:
+    assert_eq!(
+        format!("{:#}", act),
+        "Invalid lua in `config` of opt plugin `bar`: line 1, column 13: 'end' expected near '<eof>'"
+    );
+}
@@ -0,0 +1,50 @@
+local M = {}
+M.startup = function()
+local function run(id, code)
+	local fn, err_msg = loadstring(code, "=[" .. id .. "] startup")
+	local ok = fn ~= nil
+	if ok then
+		ok, err_msg = pcall(fn)
+	end
+	if not ok then
+		print("[" .. id .. "] startup error: " .. (err_msg or "-- no msg --"))
+	end
+end
+run('dummy2', 'vim.g.dummy2_startup = true')
+run('dummy4', 'vim.g.dummy4_startup = true')
+run('dummy', 'vim.g.bundle_startup = true')
+end
+M.pre_cfgs = {}
+M.cfgs = {}
+M.pre_cfgs['dummy2'] = function(...)
+vim.g.dummy2_pre = true
+end
+M.cfgs['dummy2'] = function(...)
+require('dummy2').setup()
+end
+M.pre_cfgs['dummy4'] = function(...)
+vim.g.dummy4_pre = true
+end
+M.cfgs['dummy4'] = function(...)
+require('dummy4').setup()
+end
+M.pre_cfgs['dummy'] = function(...)
+vim.g.bundle_pre = true
+end
+M.cfgs['dummy'] = function(...)
+vim.g.bundle_config = true
+end
+M.deps = {dummy={'dummy3','dummy4',},dummy1={},dummy2={'dummy4',},dummy3={},dummy4={'dummy5',},dummy5={},}
+M.plugin = {dummy1='dummy1',dummy2='dummy2',dummy3='dummy3',dummy4='dummy4',dummy5='dummy5',}
+M.plugins = {dummy={'dummy1','dummy2',},dummy1={},dummy2={},dummy3={},dummy4={},dummy5={},}
+M.mod_idx = {bundle_depends_nested_module={'dummy4',},bundle_module={'dummy',},bundle_plugin_nested_module={'dummy2',},}
+M.ev_tbl = {{event='BufEnter',pattern='*',key='BufEnter',},{event='CmdlineEnter',pattern='*',key='CmdlineEnter',},{event='LspAttach',pattern='*',key='LspAttach',},}
+M.evs = {BufEnter={'dummy',},CmdlineEnter={'dummy2',},LspAttach={'dummy4',},}
+M.ft_tbl = {'bundle_depends_nested_filetype','bundle_filetype','bundle_plugin_nested_filetype',}
+M.fts = {bundle_depends_nested_filetype={'dummy4',},bundle_filetype={'dummy',},bundle_plugin_nested_filetype={'dummy2',},}
+M.cmd_tbl = {'BundleCommand','BundleDependsNestedCommand','BundlePluginNestedCommand',}
+M.cmds = {BundleCommand={'dummy',},BundleDependsNestedCommand={'dummy4',},BundlePluginNestedCommand={'dummy2',},}
+M.key_tbl = {{mode='n',lhs='<leader>b',desc=nil,ids={'dummy',},},}
+M.ftdetect_tbl = {}
+M.lazy = {'dummy4',}
+return M
\ No newline at end of file
@@ -0,0 +1,49 @@
+local M = {}
+M.startup = function()
+local function run(id, code)
+	local fn, err_msg = loadstring(code, "=[" .. id .. "] startup")
+	local ok = fn ~= nil
+	if ok then
+		ok, err_msg = pcall(fn)
+	end
+	if not ok then
+		print("[" .. id .. "] startup error: " .. (err_msg or "-- no msg --"))
+	end
+end
+run('dummy3', 'vim.g.dummy3_startup = true')
+run('dummy1', 'vim.g.dummy1_startup = true')
+end
+M.pre_cfgs = {}
+M.cfgs = {}
+M.pre_cfgs['dummy1'] = function(...)
+vim.cmd([[
+  let s:args = json_decode('{"foo":"foo"}')
+  let g:dummy1_foo = s:args.foo
+]])
+
+end
+M.cfgs['dummy1'] = function(...)
+local args = vim.json.decode([[{"bar":1}]])
+require('dummy1').setup(args)
+
+end
+M.pre_cfgs['dummy3'] = function(...)
+vim.g.dummy3_pre = true
+end
+M.cfgs['dummy3'] = function(...)
+require('dummy3').setup()
+end
+M.deps = {dummy1={'dummy2','dummy3',},dummy2={},dummy3={'dummy4',},dummy4={},}
+M.plugin = {dummy1='dummy1',dummy2='dummy2',dummy3='dummy3',dummy4='dummy4',}
+M.plugins = {dummy1={},dummy2={},dummy3={},dummy4={},}
+M.mod_idx = {module={'dummy1',},module_nested={'dummy3',},}
+M.ev_tbl = {{event='BufReadPost',pattern='*',key='BufReadPost',},{event='InsertEnter',pattern='*',key='InsertEnter',},{event='User',pattern='VeryLazy',key='User@VeryLazy',},}
+M.evs = {BufReadPost={'dummy1',},InsertEnter={'dummy3',},['User@VeryLazy']={'dummy1',},}
+M.ft_tbl = {'filetype','filetype_nested',}
+M.fts = {filetype={'dummy1',},filetype_nested={'dummy3',},}
+M.cmd_tbl = {'Command','CommandNested',}
+M.cmds = {Command={'dummy1',},CommandNested={'dummy3',},}
+M.key_tbl = {{mode='n',lhs='<leader>o',desc=nil,ids={'dummy1',},},{mode='n',lhs='<leader>v',desc='visual',ids={'dummy1',},},{mode='x',lhs='<leader>v',desc='visual',ids={'dummy1',},},}
+M.ftdetect_tbl = {}
+M.lazy = {'dummy1',}
+return M
\ No newline at end of file
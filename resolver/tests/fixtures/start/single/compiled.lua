@@ -0,0 +1,31 @@
+local M = {}
+M.startup = function()
+local function run(id, code)
+	local fn, err_msg = loadstring(code, "=[" .. id .. "] startup")
+	local ok = fn ~= nil
+	if ok then
+		ok, err_msg = pcall(fn)
+	end
+	if not ok then
+		print("[" .. id .. "] startup error: " .. (err_msg or "-- no msg --"))
+	end
+end
+run('dummy1', 'local args = vim.json.decode([[{"start":"start"}]])\nvim.g.start = args.start\n')
+run('dummy2', 'vim.g.dummy2 = true')
+end
+M.pre_cfgs = {}
+M.cfgs = {}
+M.deps = {}
+M.plugin = {}
+M.plugins = {}
+M.mod_idx = {}
+M.ev_tbl = {}
+M.evs = {}
+M.ft_tbl = {}
+M.fts = {}
+M.cmd_tbl = {}
+M.cmds = {}
+M.key_tbl = {}
+M.ftdetect_tbl = {}
+M.lazy = {}
+return M
\ No newline at end of file
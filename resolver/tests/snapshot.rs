@@ -0,0 +1,108 @@
+//! golden snapshot tests of the generated tree.
+//!
+//! each `tests/fixtures/<name>/input.json` is resolved and generated in both modes,
+//! then compared with `tests/fixtures/<name>/{tree,single}/`.
+//! run with `OBORO_BLESS=1` to (re)write the expected files after an intended change.
+
+use oboro_resolver::{generate, resolve, Memory, OboroPluginConfig, OutputMode};
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const BLESS_ENV: &str = "OBORO_BLESS";
+
+fn fixtures() -> Vec<PathBuf> {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    let mut dirs = fs::read_dir(root)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.join("input.json").is_file())
+        .collect::<Vec<_>>();
+    dirs.sort();
+    dirs
+}
+
+/// relative path (separated by `/`) -> contents of every file under `dir`.
+fn read_tree(dir: &Path) -> BTreeMap<String, Vec<u8>> {
+    fn go(root: &Path, dir: &Path, acc: &mut BTreeMap<String, Vec<u8>>) {
+        for entry in fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                go(root, &path, acc);
+            } else {
+                let rel = path.strip_prefix(root).unwrap().to_string_lossy();
+                acc.insert(rel.replace('\\', "/"), fs::read(&path).unwrap());
+            }
+        }
+    }
+    let mut acc = BTreeMap::new();
+    if dir.is_dir() {
+        go(dir, dir, &mut acc);
+    }
+    acc
+}
+
+fn bless(dir: &Path, files: &BTreeMap<String, Vec<u8>>) {
+    if dir.exists() {
+        fs::remove_dir_all(dir).unwrap();
+    }
+    for (path, contents) in files {
+        let path = dir.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+}
+
+/// describe differences between expected and actual files.
+fn diff(exp: &BTreeMap<String, Vec<u8>>, act: &BTreeMap<String, Vec<u8>>) -> Vec<String> {
+    let mut diffs = Vec::new();
+    for (path, contents) in act {
+        match exp.get(path) {
+            None => diffs.push(format!("unexpected `{}`", path)),
+            Some(x) if x != contents => diffs.push(format!(
+                "`{}` differs\n--- expected\n{}\n--- actual\n{}",
+                path,
+                String::from_utf8_lossy(x),
+                String::from_utf8_lossy(contents)
+            )),
+            Some(_) => (),
+        }
+    }
+    for path in exp.keys().filter(|x| !act.contains_key(*x)) {
+        diffs.push(format!("missing `{}`", path));
+    }
+    diffs
+}
+
+#[test]
+fn snapshot() {
+    let blessing = env::var_os(BLESS_ENV).is_some();
+    let mut failures = Vec::new();
+    for fixture in fixtures() {
+        let text = fs::read_to_string(fixture.join("input.json")).unwrap();
+        let src = serde_json::from_str::<OboroPluginConfig>(&text).unwrap();
+        let config = resolve(&src).unwrap();
+        for (name, mode) in [
+            ("tree", OutputMode::Tree),
+            ("single", OutputMode::SingleFile),
+        ] {
+            let mut out = Memory::default();
+            generate(&config, &mut out, mode).unwrap();
+            let dir = fixture.join(name);
+            if blessing {
+                bless(&dir, &out.files);
+                continue;
+            }
+            for d in diff(&read_tree(&dir), &out.files) {
+                failures.push(format!("{}: {}", dir.display(), d));
+            }
+        }
+    }
+    assert!(
+        failures.is_empty(),
+        "{}\n\nrun with `{}=1` to update the snapshots.",
+        failures.join("\n\n"),
+        BLESS_ENV
+    );
+}